@@ -0,0 +1,215 @@
+#ifndef BREACH_PROTOCOL_H
+#define BREACH_PROTOCOL_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Error codes returned by the C interface.
+ */
+typedef enum BpError {
+  BP_ERROR_OK = 0,
+  /**
+   * A required pointer argument was null.
+   */
+  BP_ERROR_NULL_POINTER,
+  /**
+   * A token was not valid UTF-8.
+   */
+  BP_ERROR_INVALID_UTF8,
+  /**
+   * The matrix dimensions are not supported. Widths and heights from 3 to 8 are supported.
+   */
+  BP_ERROR_UNSUPPORTED_SIZE,
+  /**
+   * An index argument was out of range.
+   */
+  BP_ERROR_INDEX_OUT_OF_RANGE,
+  /**
+   * Mirrors `matrix::Error::OutOfBounds`.
+   */
+  BP_ERROR_OUT_OF_BOUNDS,
+  /**
+   * Mirrors `matrix::Error::NotActive`.
+   */
+  BP_ERROR_NOT_ACTIVE,
+  /**
+   * Mirrors `matrix::Error::AlreadySelected`.
+   */
+  BP_ERROR_ALREADY_SELECTED,
+  /**
+   * Mirrors `matrix::Error::WrongSize`.
+   */
+  BP_ERROR_WRONG_SIZE,
+  /**
+   * Mirrors `sequence::Error::NotFound`.
+   */
+  BP_ERROR_NOT_FOUND,
+  /**
+   * A path had more steps than the buffer can hold.
+   */
+  BP_ERROR_BUFFER_OVERFLOW,
+  /**
+   * Mirrors `matrix::Error::Blocked`.
+   */
+  BP_ERROR_BLOCKED,
+} BpError;
+
+/**
+ * A puzzle: a matrix of tokens, a buffer size, and a list of sequences.
+ *
+ * Only matrices with widths and heights from 3 to 8 are supported.
+ */
+typedef struct BpPuzzle BpPuzzle;
+
+/**
+ * The solutions to a puzzle.
+ */
+typedef struct BpSolutions BpSolutions;
+
+/**
+ * Create a puzzle from `width * height` tokens in row-major order.
+ *
+ * On success, `*out` is set to a new puzzle which must be freed with `bp_puzzle_free`.
+ *
+ * # Safety
+ *
+ * `tokens` must point to `width * height` valid nul-terminated strings.
+ * `out` must be a valid pointer.
+ */
+BpError bp_puzzle_new(const char *const *tokens,
+                      size_t width,
+                      size_t height,
+                      size_t buffer_size,
+                      BpPuzzle **out);
+
+/**
+ * Add a sequence of `len` patterns to a puzzle.
+ *
+ * Each pattern is a token, `*` to match any token, or tokens joined by `|` to match any one of
+ * them. Every token must appear in the puzzle's matrix.
+ *
+ * # Safety
+ *
+ * `puzzle` must have been created by `bp_puzzle_new`.
+ * `tokens` must point to `len` valid nul-terminated strings.
+ */
+BpError bp_puzzle_add_sequence(BpPuzzle *puzzle, const char *const *tokens, size_t len);
+
+/**
+ * Prevent the cell at `(x, y)` from being selected.
+ *
+ * # Safety
+ *
+ * `puzzle` must have been created by `bp_puzzle_new`.
+ */
+BpError bp_puzzle_block(BpPuzzle *puzzle, size_t x, size_t y);
+
+/**
+ * Free a puzzle. Passing null is allowed.
+ *
+ * # Safety
+ *
+ * `puzzle` must be null or have been created by `bp_puzzle_new`, and not already freed.
+ */
+void bp_puzzle_free(BpPuzzle *puzzle);
+
+/**
+ * Check a path of `len` points, given as parallel coordinate arrays, against a puzzle.
+ *
+ * If the path is illegal, `*failed_step` is set to the index of the first illegal step.
+ *
+ * # Safety
+ *
+ * `puzzle` must have been created by `bp_puzzle_new`.
+ * `xs` and `ys` must each point to `len` values. `failed_step` must be a valid pointer.
+ */
+BpError bp_puzzle_check_path(const BpPuzzle *puzzle,
+                             const size_t *xs,
+                             const size_t *ys,
+                             size_t len,
+                             size_t *failed_step);
+
+/**
+ * Find all solutions to a puzzle which complete at least one sequence.
+ *
+ * On success, `*out` is set to the solutions, which must be freed with `bp_solutions_free`.
+ *
+ * # Safety
+ *
+ * `puzzle` must have been created by `bp_puzzle_new`. `out` must be a valid pointer.
+ */
+BpError bp_solve(const BpPuzzle *puzzle, BpSolutions **out);
+
+/**
+ * The number of solutions. Returns 0 for null.
+ *
+ * # Safety
+ *
+ * `solutions` must be null or have been created by `bp_solve`.
+ */
+size_t bp_solutions_len(const BpSolutions *solutions);
+
+/**
+ * The number of steps in a solution's path. Returns 0 for null or an out-of-range index.
+ *
+ * # Safety
+ *
+ * `solutions` must be null or have been created by `bp_solve`.
+ */
+size_t bp_solution_path_len(const BpSolutions *solutions, size_t solution);
+
+/**
+ * Get the coordinates of a step in a solution's path.
+ *
+ * # Safety
+ *
+ * `solutions` must have been created by `bp_solve`. `x` and `y` must be valid pointers.
+ */
+BpError bp_solution_point(const BpSolutions *solutions,
+                          size_t solution,
+                          size_t step,
+                          size_t *x,
+                          size_t *y);
+
+/**
+ * The number of sequences a solution completes. Returns 0 for null or an out-of-range index.
+ *
+ * # Safety
+ *
+ * `solutions` must be null or have been created by `bp_solve`.
+ */
+size_t bp_solution_matches_len(const BpSolutions *solutions, size_t solution);
+
+/**
+ * Get the index, in order of addition to the puzzle, of a sequence a solution completes.
+ *
+ * # Safety
+ *
+ * `solutions` must have been created by `bp_solve`. `sequence` must be a valid pointer.
+ */
+BpError bp_solution_match(const BpSolutions *solutions,
+                          size_t solution,
+                          size_t idx,
+                          size_t *sequence);
+
+/**
+ * Free solutions. Passing null is allowed.
+ *
+ * # Safety
+ *
+ * `solutions` must be null or have been created by `bp_solve`, and not already freed.
+ */
+void bp_solutions_free(BpSolutions *solutions);
+
+/**
+ * A static, nul-terminated description of an error code.
+ */
+const char *bp_error_message(BpError error);
+
+#endif  /* BREACH_PROTOCOL_H */
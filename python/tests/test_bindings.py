@@ -0,0 +1,77 @@
+"""Tests for the Python bindings.
+
+Build and install the extension with `maturin develop`, then run with `python -m pytest python/tests`.
+"""
+
+import pytest
+
+import breach_protocol
+
+MATRIX = [
+    ["1A", "2B", "3C"],
+    ["2B", "3C", "1A"],
+    ["3C", "1A", "2B"],
+]
+
+
+def test_solve():
+    puzzle = breach_protocol.Puzzle(MATRIX, 3, [["2B", "1A"]])
+    solutions = puzzle.solve()
+    assert solutions
+    assert {
+        "path": [(1, 0), (1, 2), (0, 2)],
+        "buffer": ["2B", "1A", "3C"],
+        "matches": [0],
+    } in solutions
+    for solution in solutions:
+        assert solution["matches"] == [0]
+        assert breach_protocol.sequence_matched(["2B", "1A"], solution["buffer"])
+
+
+def test_validate():
+    puzzle = breach_protocol.Puzzle(MATRIX, 3)
+    puzzle.add_sequence(["2B", "1A"])
+
+    valid = puzzle.validate([(1, 0), (1, 2)])
+    assert valid["valid"]
+    assert valid["error"] is None
+    assert valid["failed_step"] is None
+    assert valid["matches"] == [0]
+
+    # (1, 0) pivots to column 1, so (2, 2) is not active
+    invalid = puzzle.validate([(1, 0), (2, 2)])
+    assert not invalid["valid"]
+    assert invalid["failed_step"] == 1
+    assert "not a member of the active set" in invalid["error"]
+    assert invalid["path"] == [(1, 0)]
+
+
+def test_block():
+    puzzle = breach_protocol.Puzzle(MATRIX, 3, [["2B", "1A"]])
+    puzzle.block(1, 0)
+    for solution in puzzle.solve():
+        assert (1, 0) not in solution["path"]
+    invalid = puzzle.validate([(1, 0)])
+    assert "blocked" in invalid["error"]
+    with pytest.raises(ValueError, match="out of bounds"):
+        puzzle.block(3, 0)
+
+
+def test_sequence_matched():
+    assert breach_protocol.sequence_matched(["1A", "2B"], ["3C", "1A", "2B"])
+    assert not breach_protocol.sequence_matched(["2B", "1A"], ["1A", "2B", "3C"])
+    # alternatives and wildcards need not appear in the buffer
+    assert breach_protocol.sequence_matched(["1A|FF"], ["1A"])
+    assert breach_protocol.sequence_matched(["FF|2B", "*"], ["1A", "2B", "3C"])
+    assert not breach_protocol.sequence_matched(["FF|7A"], ["1A", "2B"])
+
+
+def test_errors():
+    with pytest.raises(ValueError, match="same length"):
+        breach_protocol.Puzzle([["1A", "2B"], ["1A"]], 3)
+    with pytest.raises(ValueError, match="unsupported"):
+        breach_protocol.Puzzle([["1A", "2B"], ["1A", "2B"]], 3)
+    with pytest.raises(ValueError, match="unsupported"):
+        breach_protocol.Puzzle([["1A"] * 9], 3)
+    with pytest.raises(ValueError, match="not found"):
+        breach_protocol.Puzzle(MATRIX, 3, [["FF"]])
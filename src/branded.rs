@@ -0,0 +1,253 @@
+use std::{borrow::Borrow, fmt, marker::PhantomData, ops::Deref};
+
+use crate::interner::{Interned, Interner, Lookup};
+
+/// An invariant lifetime, unique to a single call of [`Interner::brand`].
+///
+/// Because the lifetime is invariant, the compiler can never unify two different brands, so
+/// handles carrying different brands can't be compared or mixed.
+type Brand<'id> = PhantomData<fn(&'id ()) -> &'id ()>;
+
+/// A view of an [`Interner`] whose handles are branded with a unique lifetime.
+///
+/// Created by [`Interner::brand`]. Matrices and sequences built through a branded interner hold
+/// branded handles:
+///
+/// ```rust
+/// # use breach_protocol::{BreachProtocol, Interner, Matrix, Sequence};
+/// let mut interner = Interner::new();
+/// interner.extend(vec!["1C".to_owned(), "55".to_owned()]);
+///
+/// let solutions = interner.brand(|interner| {
+///     let mut matrix = Matrix::<_, 2, 2>::new(interner, ["1C", "55", "55", "1C"]).unwrap();
+///     let sequences = [Sequence::new(interner, ["1C", "55"]).unwrap()];
+///     BreachProtocol::new(2).solve(&mut matrix, &sequences).len()
+/// });
+/// assert_eq!(solutions, 1);
+/// ```
+///
+/// so a matrix and sequences from different interners can't be solved together:
+///
+/// ```rust,compile_fail
+/// # use breach_protocol::{BreachProtocol, Interner, Matrix, Sequence};
+/// let mut interner = Interner::new();
+/// interner.extend(vec!["1C".to_owned(), "55".to_owned()]);
+/// let clone = interner.clone();
+///
+/// interner.brand(|interner| {
+///     clone.brand(|clone| {
+///         let mut matrix = Matrix::<_, 2, 2>::new(interner, ["1C", "55", "55", "1C"]).unwrap();
+///         let sequences = [Sequence::new(clone, ["1C", "55"]).unwrap()];
+///         // error: the two brands can't be unified
+///         BreachProtocol::new(2).solve(&mut matrix, &sequences);
+///     })
+/// });
+/// ```
+pub struct BrandedInterner<'a, 'id, T> {
+    interner: &'a Interner<T>,
+    _brand: Brand<'id>,
+}
+
+/// An interned value branded with the identity of its interner.
+///
+/// Unlike [`Interned`], whose comparisons silently fail when handles come from different
+/// interners, branded handles from different interners have different types: comparing them
+/// is a compile error. Handles of the same brand compare by index alone, and so also implement
+/// `Ord` and `Hash`.
+///
+/// ```rust,compile_fail
+/// # use breach_protocol::Interner;
+/// let mut interner = Interner::new();
+/// interner.insert('a');
+/// let clone = interner.clone();
+///
+/// interner.brand(|interner| {
+///     clone.brand(|clone| {
+///         // error: the two brands can't be unified
+///         interner.get(&'a') == clone.get(&'a')
+///     })
+/// });
+/// ```
+pub struct BrandedInterned<'a, 'id, T> {
+    interner: &'a Interner<T>,
+    idx: usize,
+    _brand: Brand<'id>,
+}
+
+impl<T> Interner<T> {
+    /// Call `f` with a branded view of this interner.
+    ///
+    /// Every call produces a fresh brand, so handles produced by different calls can never be
+    /// confused, even when the calls are on the same interner.
+    ///
+    /// ```rust
+    /// # use breach_protocol::Interner;
+    /// let mut interner = Interner::new();
+    /// interner.extend(vec!['a', 'b']);
+    ///
+    /// let ordered = interner.brand(|interner| {
+    ///     let a = interner.get(&'a').unwrap();
+    ///     let b = interner.get(&'b').unwrap();
+    ///     a < b
+    /// });
+    /// assert!(ordered);
+    /// ```
+    pub fn brand<R>(&self, f: impl for<'id> FnOnce(BrandedInterner<'_, 'id, T>) -> R) -> R {
+        f(BrandedInterner {
+            interner: self,
+            _brand: PhantomData,
+        })
+    }
+}
+
+impl<'a, 'id, T> BrandedInterner<'a, 'id, T> {
+    /// The underlying interner
+    pub fn interner(&self) -> &'a Interner<T> {
+        self.interner
+    }
+
+    /// Get the branded version of an interned value, if it belongs to this interner.
+    pub fn adopt(&self, value: Interned<'a, T>) -> Option<BrandedInterned<'a, 'id, T>> {
+        std::ptr::eq(value.interner(), self.interner).then_some(BrandedInterned {
+            interner: self.interner,
+            idx: value.index(),
+            _brand: PhantomData,
+        })
+    }
+}
+
+impl<'a, 'id, T> BrandedInterner<'a, 'id, T>
+where
+    T: Ord,
+{
+    /// Get the branded version of the provided value, if it is available in the interner.
+    pub fn get<Q>(&self, value: &Q) -> Option<BrandedInterned<'a, 'id, T>>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.interner.get(value).and_then(|value| self.adopt(value))
+    }
+}
+
+impl<'a, 'id> Lookup for BrandedInterner<'a, 'id, String> {
+    type Token = BrandedInterned<'a, 'id, String>;
+
+    fn lookup(self, item: &str) -> Option<Self::Token> {
+        self.get(item)
+    }
+}
+
+impl<'a, 'id, T> Clone for BrandedInterner<'a, 'id, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, 'id, T> Copy for BrandedInterner<'a, 'id, T> {}
+
+impl<'a, 'id, T> fmt::Debug for BrandedInterner<'a, 'id, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BrandedInterner")
+            .field(self.interner)
+            .finish()
+    }
+}
+
+impl<'a, 'id, T> BrandedInterned<'a, 'id, T> {
+    /// Forget the brand of this value, for use with APIs which accept [`Interned`].
+    pub fn unbrand(self) -> Interned<'a, T> {
+        self.interner
+            .get_index(self.idx)
+            .expect("branded values are always in range")
+    }
+}
+
+impl<'a, 'id, T> fmt::Debug for BrandedInterned<'a, 'id, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BrandedInterned")
+            .field("idx", &self.idx)
+            .finish()
+    }
+}
+
+impl<'a, 'id, T> Deref for BrandedInterned<'a, 'id, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.interner
+            .value(self.idx)
+            .expect("branded values are always in range")
+    }
+}
+
+// The brand guarantees that both values come from the same interner, so comparisons need only
+// consider the index.
+impl<'a, 'id, T> PartialEq for BrandedInterned<'a, 'id, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.idx == other.idx
+    }
+}
+
+impl<'a, 'id, T> Eq for BrandedInterned<'a, 'id, T> {}
+
+impl<'a, 'id, T> PartialOrd for BrandedInterned<'a, 'id, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, 'id, T> Ord for BrandedInterned<'a, 'id, T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.idx.cmp(&other.idx)
+    }
+}
+
+impl<'a, 'id, T> std::hash::Hash for BrandedInterned<'a, 'id, T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.idx.hash(state);
+    }
+}
+
+impl<'a, 'id, T> Clone for BrandedInterned<'a, 'id, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, 'id, T> Copy for BrandedInterned<'a, 'id, T> {}
+
+impl<'a, 'id, T> fmt::Display for BrandedInterned<'a, 'id, T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branded_roundtrip() {
+        let mut interner = Interner::new();
+        interner.extend(vec!["1C".to_owned(), "55".to_owned()]);
+        let other = interner.clone();
+
+        interner.brand(|branded| {
+            let c1 = branded.get("1C").unwrap();
+            let c55 = branded.get("55").unwrap();
+            assert_ne!(c1, c55);
+            assert!(c1 < c55);
+            assert_eq!(*c1, "1C");
+            assert_eq!(branded.adopt(c1.unbrand()), Some(c1));
+            // values from other interners are rejected at runtime when adopted
+            assert_eq!(branded.adopt(other.get("1C").unwrap()), None);
+        });
+    }
+}
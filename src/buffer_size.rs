@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use crate::{matrix::Matrix, rules::Rules, sequence::Sequence, BreachProtocol};
+
+/// The smallest buffers which complete each subset of a list of sequences.
+///
+/// Produced by [`BreachProtocol::buffer_sizes`][crate::BreachProtocol::buffer_sizes]. Sizes count
+/// every selection in the buffer, including any already made on the matrix when searching began.
+///
+/// ```rust
+/// # use breach_protocol::{BreachProtocol, Code, Matrix, Sequence};
+/// let codes = Code::parse_list("1C 55 BD 55 BD 1C BD 1C 55").unwrap();
+/// let mut matrix = Matrix::<_, 3, 3>::from_values(codes).unwrap();
+/// let sequences = [
+///     Sequence::from_values(Code::parse_list("55 1C").unwrap()),
+///     Sequence::from_values(Code::parse_list("1C BD").unwrap()),
+/// ];
+///
+/// let sizes = BreachProtocol::new(6).buffer_sizes(&mut matrix, &sequences).unwrap();
+/// assert_eq!(sizes.for_subset([0]), Some(2));
+/// assert_eq!(sizes.for_subset([1]), Some(2));
+/// assert_eq!(sizes.all(), Some(3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufferSizes {
+    sequences: usize,
+    /// The shortest buffer which completes exactly each reachable set of sequences.
+    best: HashMap<u64, usize>,
+}
+
+impl BufferSizes {
+    /// The smallest buffer size which completes every sequence of `subset`, given by index.
+    ///
+    /// `None` if no buffer up to the search limit does so, or if an index is out of range.
+    pub fn for_subset(&self, subset: impl IntoIterator<Item = usize>) -> Option<usize> {
+        let mut want = 0_u64;
+        for idx in subset {
+            if idx >= self.sequences {
+                return None;
+            }
+            want |= 1 << idx;
+        }
+        self.best
+            .iter()
+            .filter(|&(&mask, _)| mask & want == want)
+            .map(|(_, &size)| size)
+            .min()
+    }
+
+    /// The smallest buffer size which completes every sequence.
+    pub fn all(&self) -> Option<usize> {
+        self.for_subset(0..self.sequences)
+    }
+}
+
+/// Search every path up to the buffer size of `protocol`, recording the shortest buffer which
+/// completes each set of sequences.
+///
+/// A single search serves every size: a sequence is complete as soon as the buffer ends with
+/// it, and stays complete as the buffer grows.
+pub(crate) fn search<T, R, const WIDTH: usize, const HEIGHT: usize>(
+    protocol: &BreachProtocol,
+    matrix: &mut Matrix<T, WIDTH, HEIGHT, R>,
+    sequences: &[Sequence<T>],
+) -> Result<BufferSizes, Error>
+where
+    T: Copy + Eq,
+    R: Rules,
+{
+    if sequences.len() > u64::BITS as usize {
+        return Err(Error::TooManySequences(sequences.len()));
+    }
+    let everything = u64::MAX
+        .checked_shr(u64::BITS - sequences.len() as u32)
+        .unwrap_or_default();
+
+    let mut sizes = BufferSizes {
+        sequences: sequences.len(),
+        best: HashMap::new(),
+    };
+    protocol.solve_inner(matrix, &mut |matrix| {
+        let buffer: Vec<T> = matrix.selected_values().collect();
+        let matched = sequences
+            .iter()
+            .enumerate()
+            .filter(|(_, sequence)| sequence.is_matched(&buffer))
+            .fold(0, |mask, (idx, _)| mask | 1 << idx);
+        let best = sizes.best.entry(matched).or_insert(buffer.len());
+        *best = (*best).min(buffer.len());
+        // a longer buffer can't complete any more
+        matched != everything
+    });
+    Ok(sizes)
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("buffer sizes can only be found for up to 64 sequences, but {0} were given")]
+    TooManySequences(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::Code;
+
+    fn codes(s: &str) -> Vec<Code> {
+        Code::parse_list(s).unwrap()
+    }
+
+    #[test]
+    fn subsets() {
+        let mut matrix =
+            Matrix::<_, 3, 3>::from_values(codes("1C 55 BD 55 BD 1C BD 1C 55")).unwrap();
+        let sequences = [
+            Sequence::from_values(codes("55 1C")),
+            Sequence::from_values(codes("1C BD")),
+            Sequence::from_values(codes("E9")),
+        ];
+
+        let sizes = search(&BreachProtocol::new(6), &mut matrix, &sequences).unwrap();
+        assert_eq!(sizes.for_subset([]), Some(0));
+        assert_eq!(sizes.for_subset([0]), Some(2));
+        assert_eq!(sizes.for_subset([1]), Some(2));
+        assert_eq!(sizes.for_subset([0, 1]), Some(3));
+        assert_eq!(sizes.for_subset([2]), None);
+        assert_eq!(sizes.for_subset([3]), None);
+        assert_eq!(sizes.all(), None);
+        // the matrix is restored once searching completes
+        assert_eq!(matrix.selected_len(), 0);
+    }
+
+    #[test]
+    fn counts_existing_selections() {
+        let mut matrix =
+            Matrix::<_, 3, 3>::from_values(codes("1C 55 BD 55 BD 1C BD 1C 55")).unwrap();
+        matrix.select(1, 0).unwrap();
+        let sequences = [Sequence::from_values(codes("55"))];
+
+        let sizes = search(&BreachProtocol::new(1), &mut matrix, &sequences).unwrap();
+        assert_eq!(sizes.all(), Some(1));
+        assert_eq!(matrix.selections(), [(1, 0)]);
+    }
+
+    #[test]
+    fn too_many_sequences() {
+        let mut matrix =
+            Matrix::<_, 3, 3>::from_values(codes("1C 55 BD 55 BD 1C BD 1C 55")).unwrap();
+        let sequences: Vec<_> = (0..65)
+            .map(|_| Sequence::from_values(codes("55")))
+            .collect();
+        assert!(matches!(
+            search(&BreachProtocol::new(2), &mut matrix, &sequences),
+            Err(Error::TooManySequences(65))
+        ));
+        assert!(search(&BreachProtocol::new(2), &mut matrix, &sequences[..64]).is_ok());
+    }
+}
@@ -0,0 +1,104 @@
+use std::{fmt, str::FromStr};
+
+/// A byte code: the hex pair displayed in a cell of the game, such as `1C` or `E9`.
+///
+/// Codes are plain bytes, so they can be used as the tokens of a [`Matrix`][crate::Matrix] and
+/// [`Sequence`][crate::Sequence] without any interner.
+///
+/// ```rust
+/// # use breach_protocol::{BreachProtocol, Code, Matrix, Sequence};
+/// let codes = Code::parse_list("1C 55 55 1C").unwrap();
+/// let mut matrix = Matrix::<_, 2, 2>::from_values(codes).unwrap();
+/// let sequences = [Sequence::from_values(Code::parse_list("1C 55").unwrap())];
+///
+/// let solutions = BreachProtocol::new(2).solve(&mut matrix, &sequences);
+/// assert_eq!(solutions[0].path(), [(0, 0), (0, 1)]);
+/// assert_eq!(solutions[0].buffer()[1].to_string(), "55");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Code(pub u8);
+
+impl Code {
+    /// Parse a whitespace-separated list of codes.
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, Error> {
+        s.split_ascii_whitespace().map(str::parse).collect()
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02X}", self.0)
+    }
+}
+
+impl FromStr for Code {
+    type Err = Error;
+
+    /// Parse exactly two hex digits, in either case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 2 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(Error::Invalid(s.to_owned()));
+        }
+        u8::from_str_radix(s, 16)
+            .map(Code)
+            .map_err(|_| Error::Invalid(s.to_owned()))
+    }
+}
+
+impl From<u8> for Code {
+    fn from(value: u8) -> Self {
+        Self(value)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("not a hex pair: \"{0}\"")]
+    Invalid(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BreachProtocol, Matrix, Sequence};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1C", Some(0x1c))]
+    #[case("e9", Some(0xe9))]
+    #[case("FF", Some(0xff))]
+    #[case("7", None)]
+    #[case("+7", None)]
+    #[case("1C5", None)]
+    #[case("ZZ", None)]
+    fn parse(#[case] input: &str, #[case] expect: Option<u8>) {
+        assert_eq!(input.parse::<Code>().ok(), expect.map(Code));
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        for byte in 0..=u8::MAX {
+            assert_eq!(Code(byte).to_string().parse::<Code>().unwrap(), Code(byte));
+        }
+        assert_eq!(Code(0x0a).to_string(), "0A");
+    }
+
+    #[test]
+    fn solve() {
+        let mut matrix =
+            Matrix::<_, 3, 3>::from_values(Code::parse_list("1C 55 BD 55 BD 1C BD 1C 55").unwrap())
+                .unwrap();
+        let sequences = [
+            Sequence::from_values(Code::parse_list("55 1C").unwrap()),
+            Sequence::from_values(Code::parse_list("1C BD BD").unwrap()),
+        ];
+        let solutions = BreachProtocol::new(3).solve(&mut matrix, &sequences);
+        assert!(solutions
+            .iter()
+            .any(|solution| solution.path() == [(1, 0), (1, 2), (0, 2)]
+                && solution.buffer() == Code::parse_list("55 1C BD").unwrap()
+                && solution.matches() == [0]));
+        assert_eq!(matrix.selected_len(), 0);
+    }
+}
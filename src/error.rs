@@ -0,0 +1,79 @@
+use crate::{buffer_size, code, grid, matrix, replay, sequence, validate};
+
+/// Any error produced by this crate.
+///
+/// Each operation returns the specific error of its module, such as [`MatrixError`] from
+/// [`Matrix::select`][crate::Matrix::select]; all of them convert into this type with `?`.
+/// Variants display as the error they wrap, and [`source`][std::error::Error::source] continues
+/// the chain from there.
+///
+/// [`MatrixError`]: crate::MatrixError
+///
+/// ```rust
+/// # use breach_protocol::{Error, Interner, Matrix, MatrixError, Sequence};
+/// fn build(interner: &Interner<String>) -> Result<(), Error> {
+///     let mut matrix = Matrix::<_, 2, 2>::new(interner, ["1C", "55", "55", "1C"])?;
+///     matrix.select(0, 1)?;
+///     Ok(())
+/// }
+///
+/// let interner = ["1C", "55"].into_iter().map(String::from).collect();
+/// assert!(matches!(
+///     build(&interner),
+///     Err(Error::Matrix(MatrixError::NotActive { x: 0, y: 1, .. }))
+/// ));
+/// ```
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// Constructing a matrix, or making an illegal move on one
+    #[error(transparent)]
+    Matrix(#[from] matrix::Error),
+    /// Interning the values of a sequence or matrix
+    #[error(transparent)]
+    Sequence(#[from] sequence::Error),
+    /// Parsing a [`Code`][crate::Code]
+    #[error(transparent)]
+    Code(#[from] code::Error),
+    /// Converting rows into a [`Grid`][crate::Grid]
+    #[error(transparent)]
+    Grid(#[from] grid::Error),
+    /// Parsing or verifying a [`Replay`][crate::Replay]
+    #[error(transparent)]
+    Replay(#[from] replay::Error),
+    /// Validating a path
+    #[error(transparent)]
+    Validation(#[from] validate::Error),
+    /// Finding the [`BufferSizes`][crate::BufferSizes] of a puzzle
+    #[error(transparent)]
+    BufferSize(#[from] buffer_size::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn source_chain() {
+        let err: Error = replay::Error::Invalid(validate::Error::IllegalMove {
+            step: 2,
+            source: matrix::Error::AlreadySelected { x: 1, y: 0 },
+        })
+        .into();
+        let mut chain = vec![err.to_string()];
+        let mut source = err.source();
+        while let Some(err) = source {
+            chain.push(err.to_string());
+            source = err.source();
+        }
+        assert_eq!(
+            chain,
+            [
+                "replay is not a valid path through the puzzle",
+                "step 2 is illegal",
+                "the point `(1, 0)` has already been selected",
+            ]
+        );
+    }
+}
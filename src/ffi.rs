@@ -0,0 +1,391 @@
+// C ABI for embedding the solver.
+//
+// The header `include/breach_protocol.h` is generated from this module by `cbindgen`.
+
+use std::{
+    ffi::{c_char, CStr},
+    ptr,
+};
+
+use crate::{
+    matrix,
+    owned::{self, OwnedPuzzle, OwnedSolution},
+    sequence, validate,
+};
+
+/// Error codes returned by the C interface.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BpError {
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer,
+    /// A token was not valid UTF-8.
+    InvalidUtf8,
+    /// The matrix dimensions are not supported. Widths and heights from 3 to 8 are supported.
+    UnsupportedSize,
+    /// An index argument was out of range.
+    IndexOutOfRange,
+    /// Mirrors `matrix::Error::OutOfBounds`.
+    OutOfBounds,
+    /// Mirrors `matrix::Error::NotActive`.
+    NotActive,
+    /// Mirrors `matrix::Error::AlreadySelected`.
+    AlreadySelected,
+    /// Mirrors `matrix::Error::WrongSize`.
+    WrongSize,
+    /// Mirrors `sequence::Error::NotFound`.
+    NotFound,
+    /// A path had more steps than the buffer can hold.
+    BufferOverflow,
+    /// Mirrors `matrix::Error::Blocked`.
+    Blocked,
+}
+
+impl From<sequence::Error> for BpError {
+    fn from(err: sequence::Error) -> Self {
+        match err {
+            sequence::Error::NotFound(_) => BpError::NotFound,
+        }
+    }
+}
+
+impl From<matrix::Error> for BpError {
+    fn from(err: matrix::Error) -> Self {
+        match err {
+            matrix::Error::OutOfBounds { .. } => BpError::OutOfBounds,
+            matrix::Error::NotActive { .. } => BpError::NotActive,
+            matrix::Error::AlreadySelected { .. } => BpError::AlreadySelected,
+            matrix::Error::Blocked { .. } => BpError::Blocked,
+            // the bindings only use the standard rules, which never pivot on other sets
+            matrix::Error::NoPivot { .. } => BpError::NotActive,
+            matrix::Error::WrongSize { .. } => BpError::WrongSize,
+            matrix::Error::Interning(err) => err.into(),
+        }
+    }
+}
+
+impl From<owned::Error> for BpError {
+    fn from(err: owned::Error) -> Self {
+        match err {
+            owned::Error::UnsupportedSize { .. } => BpError::UnsupportedSize,
+            owned::Error::Matrix(err) => err.into(),
+            owned::Error::Sequence(err) => err.into(),
+        }
+    }
+}
+
+impl From<validate::Error> for BpError {
+    fn from(err: validate::Error) -> Self {
+        match err {
+            validate::Error::BufferOverflow { .. } => BpError::BufferOverflow,
+            validate::Error::IllegalMove { source, .. } => source.into(),
+        }
+    }
+}
+
+/// A puzzle: a matrix of tokens, a buffer size, and a list of sequences.
+///
+/// Only matrices with widths and heights from 3 to 8 are supported.
+pub struct BpPuzzle(OwnedPuzzle);
+
+/// The solutions to a puzzle.
+pub struct BpSolutions(Vec<OwnedSolution>);
+
+/// Copy an array of C strings.
+///
+/// # Safety
+///
+/// `tokens` must point to `len` valid nul-terminated strings.
+unsafe fn read_tokens(tokens: *const *const c_char, len: usize) -> Result<Vec<String>, BpError> {
+    if tokens.is_null() && len > 0 {
+        return Err(BpError::NullPointer);
+    }
+    let mut out = Vec::with_capacity(len);
+    for idx in 0..len {
+        let token = *tokens.add(idx);
+        if token.is_null() {
+            return Err(BpError::NullPointer);
+        }
+        let token = CStr::from_ptr(token)
+            .to_str()
+            .map_err(|_| BpError::InvalidUtf8)?;
+        out.push(token.to_owned());
+    }
+    Ok(out)
+}
+
+fn code(result: Result<(), BpError>) -> BpError {
+    match result {
+        Ok(()) => BpError::Ok,
+        Err(err) => err,
+    }
+}
+
+/// Create a puzzle from `width * height` tokens in row-major order.
+///
+/// On success, `*out` is set to a new puzzle which must be freed with `bp_puzzle_free`.
+///
+/// # Safety
+///
+/// `tokens` must point to `width * height` valid nul-terminated strings.
+/// `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bp_puzzle_new(
+    tokens: *const *const c_char,
+    width: usize,
+    height: usize,
+    buffer_size: usize,
+    out: *mut *mut BpPuzzle,
+) -> BpError {
+    if out.is_null() {
+        return BpError::NullPointer;
+    }
+    *out = ptr::null_mut();
+    code((|| {
+        let values = read_tokens(tokens, width.saturating_mul(height))?;
+        let puzzle = OwnedPuzzle::new(values, width, height, buffer_size)?;
+        *out = Box::into_raw(Box::new(BpPuzzle(puzzle)));
+        Ok(())
+    })())
+}
+
+/// Add a sequence of `len` patterns to a puzzle.
+///
+/// Each pattern is a token, `*` to match any token, or tokens joined by `|` to match any one of
+/// them. Every token must appear in the puzzle's matrix.
+///
+/// # Safety
+///
+/// `puzzle` must have been created by `bp_puzzle_new`.
+/// `tokens` must point to `len` valid nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn bp_puzzle_add_sequence(
+    puzzle: *mut BpPuzzle,
+    tokens: *const *const c_char,
+    len: usize,
+) -> BpError {
+    let Some(puzzle) = puzzle.as_mut() else {
+        return BpError::NullPointer;
+    };
+    code((|| {
+        let items = read_tokens(tokens, len)?;
+        puzzle.0.add_sequence(items)?;
+        Ok(())
+    })())
+}
+
+/// Prevent the cell at `(x, y)` from being selected.
+///
+/// # Safety
+///
+/// `puzzle` must have been created by `bp_puzzle_new`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_puzzle_block(puzzle: *mut BpPuzzle, x: usize, y: usize) -> BpError {
+    let Some(puzzle) = puzzle.as_mut() else {
+        return BpError::NullPointer;
+    };
+    code(puzzle.0.block(x, y).map_err(Into::into))
+}
+
+/// Free a puzzle. Passing null is allowed.
+///
+/// # Safety
+///
+/// `puzzle` must be null or have been created by `bp_puzzle_new`, and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn bp_puzzle_free(puzzle: *mut BpPuzzle) {
+    if !puzzle.is_null() {
+        drop(Box::from_raw(puzzle));
+    }
+}
+
+/// Check a path of `len` points, given as parallel coordinate arrays, against a puzzle.
+///
+/// If the path is illegal, `*failed_step` is set to the index of the first illegal step.
+///
+/// # Safety
+///
+/// `puzzle` must have been created by `bp_puzzle_new`.
+/// `xs` and `ys` must each point to `len` values. `failed_step` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bp_puzzle_check_path(
+    puzzle: *const BpPuzzle,
+    xs: *const usize,
+    ys: *const usize,
+    len: usize,
+    failed_step: *mut usize,
+) -> BpError {
+    let (Some(puzzle), Some(failed_step)) = (puzzle.as_ref(), failed_step.as_mut()) else {
+        return BpError::NullPointer;
+    };
+    if len > 0 && (xs.is_null() || ys.is_null()) {
+        return BpError::NullPointer;
+    }
+    let path: Vec<_> = (0..len).map(|idx| (*xs.add(idx), *ys.add(idx))).collect();
+    code((|| match puzzle.0.check_path(&path)? {
+        (_, None) => Ok(()),
+        (_, Some(err)) => {
+            *failed_step = match err {
+                validate::Error::BufferOverflow { step, .. }
+                | validate::Error::IllegalMove { step, .. } => step,
+            };
+            Err(err.into())
+        }
+    })())
+}
+
+/// Find all solutions to a puzzle which complete at least one sequence.
+///
+/// On success, `*out` is set to the solutions, which must be freed with `bp_solutions_free`.
+///
+/// # Safety
+///
+/// `puzzle` must have been created by `bp_puzzle_new`. `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bp_solve(puzzle: *const BpPuzzle, out: *mut *mut BpSolutions) -> BpError {
+    let Some(puzzle) = puzzle.as_ref() else {
+        return BpError::NullPointer;
+    };
+    if out.is_null() {
+        return BpError::NullPointer;
+    }
+    *out = ptr::null_mut();
+    code((|| {
+        let solutions = puzzle.0.solve()?;
+        *out = Box::into_raw(Box::new(BpSolutions(solutions)));
+        Ok(())
+    })())
+}
+
+/// The number of solutions. Returns 0 for null.
+///
+/// # Safety
+///
+/// `solutions` must be null or have been created by `bp_solve`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_solutions_len(solutions: *const BpSolutions) -> usize {
+    solutions.as_ref().map_or(0, |solutions| solutions.0.len())
+}
+
+/// The number of steps in a solution's path. Returns 0 for null or an out-of-range index.
+///
+/// # Safety
+///
+/// `solutions` must be null or have been created by `bp_solve`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_solution_path_len(
+    solutions: *const BpSolutions,
+    solution: usize,
+) -> usize {
+    solutions
+        .as_ref()
+        .and_then(|solutions| solutions.0.get(solution))
+        .map_or(0, |solution| solution.path.len())
+}
+
+/// Get the coordinates of a step in a solution's path.
+///
+/// # Safety
+///
+/// `solutions` must have been created by `bp_solve`. `x` and `y` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn bp_solution_point(
+    solutions: *const BpSolutions,
+    solution: usize,
+    step: usize,
+    x: *mut usize,
+    y: *mut usize,
+) -> BpError {
+    let (Some(solutions), Some(x), Some(y)) = (solutions.as_ref(), x.as_mut(), y.as_mut()) else {
+        return BpError::NullPointer;
+    };
+    match solutions
+        .0
+        .get(solution)
+        .and_then(|solution| solution.path.get(step))
+    {
+        Some(&(px, py)) => {
+            *x = px;
+            *y = py;
+            BpError::Ok
+        }
+        None => BpError::IndexOutOfRange,
+    }
+}
+
+/// The number of sequences a solution completes. Returns 0 for null or an out-of-range index.
+///
+/// # Safety
+///
+/// `solutions` must be null or have been created by `bp_solve`.
+#[no_mangle]
+pub unsafe extern "C" fn bp_solution_matches_len(
+    solutions: *const BpSolutions,
+    solution: usize,
+) -> usize {
+    solutions
+        .as_ref()
+        .and_then(|solutions| solutions.0.get(solution))
+        .map_or(0, |solution| solution.matches.len())
+}
+
+/// Get the index, in order of addition to the puzzle, of a sequence a solution completes.
+///
+/// # Safety
+///
+/// `solutions` must have been created by `bp_solve`. `sequence` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bp_solution_match(
+    solutions: *const BpSolutions,
+    solution: usize,
+    idx: usize,
+    sequence: *mut usize,
+) -> BpError {
+    let (Some(solutions), Some(sequence)) = (solutions.as_ref(), sequence.as_mut()) else {
+        return BpError::NullPointer;
+    };
+    match solutions
+        .0
+        .get(solution)
+        .and_then(|solution| solution.matches.get(idx))
+    {
+        Some(&matched) => {
+            *sequence = matched;
+            BpError::Ok
+        }
+        None => BpError::IndexOutOfRange,
+    }
+}
+
+/// Free solutions. Passing null is allowed.
+///
+/// # Safety
+///
+/// `solutions` must be null or have been created by `bp_solve`, and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn bp_solutions_free(solutions: *mut BpSolutions) {
+    if !solutions.is_null() {
+        drop(Box::from_raw(solutions));
+    }
+}
+
+/// A static, nul-terminated description of an error code.
+#[no_mangle]
+pub extern "C" fn bp_error_message(error: BpError) -> *const c_char {
+    let message: &'static CStr = match error {
+        BpError::Ok => c"no error",
+        BpError::NullPointer => c"a required pointer was null",
+        BpError::InvalidUtf8 => c"a token was not valid UTF-8",
+        BpError::UnsupportedSize => c"unsupported matrix dimensions",
+        BpError::IndexOutOfRange => c"index out of range",
+        BpError::OutOfBounds => c"point is out of bounds",
+        BpError::NotActive => c"point is not a member of the active set",
+        BpError::AlreadySelected => c"point has already been selected",
+        BpError::WrongSize => c"wrong number of tokens to construct matrix",
+        BpError::NotFound => c"sequence token not found in matrix",
+        BpError::BufferOverflow => c"path exceeds the buffer size",
+        BpError::Blocked => c"point is blocked",
+    };
+    message.as_ptr()
+}
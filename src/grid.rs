@@ -1,5 +1,7 @@
 use std::ops::{Index, IndexMut};
 
+use crate::matrix::Active;
+
 /// A representation of a 2d grid.
 ///
 /// For indexing operations on this grid, `(0, 0)` is the top left corner.
@@ -15,13 +17,94 @@ where
     }
 }
 
+impl<T, const WIDTH: usize, const HEIGHT: usize> Default for Grid<T, WIDTH, HEIGHT>
+where
+    T: Default + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T, const WIDTH: usize, const HEIGHT: usize> Grid<T, WIDTH, HEIGHT> {
+    /// Construct a grid from a vector of values in row-major order,
+    /// or `None` if the vector is the wrong size.
+    pub(crate) fn from_vec(values: Vec<T>) -> Option<Self> {
+        (values.len() == WIDTH * HEIGHT).then_some(Grid(values))
+    }
+
+    /// Construct a grid by calling `f(x, y)` for each point, in row-major order.
+    pub fn from_fn(mut f: impl FnMut(usize, usize) -> T) -> Self {
+        Grid(Self::points().map(|(x, y)| f(x, y)).collect())
+    }
+
     /// Get the internal index where the desired value is stored,
     /// or `None` if it is out of bounds.
     pub fn idx(x: usize, y: usize) -> Option<usize> {
         (x < WIDTH && y < HEIGHT).then_some((y * WIDTH) + x)
     }
 
+    /// Iterate over every point of a grid of these dimensions, in row-major order.
+    pub fn points() -> impl Iterator<Item = (usize, usize)> {
+        (0..HEIGHT).flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+    }
+
+    /// Iterate over the points of a grid of these dimensions which are members of an active set,
+    /// in row-major order.
+    pub fn line_points(active: Active) -> impl Iterator<Item = (usize, usize)> {
+        let points: Box<dyn Iterator<Item = (usize, usize)>> = match active {
+            // lines are common enough that it's worth not scanning the whole grid
+            Active::Row(y) if y < HEIGHT => Box::new((0..WIDTH).map(move |x| (x, y))),
+            Active::Column(x) if x < WIDTH => Box::new((0..HEIGHT).map(move |y| (x, y))),
+            _ => Box::new(
+                (0..HEIGHT)
+                    .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+                    .filter(move |&(x, y)| active.contains(x, y)),
+            ),
+        };
+        points
+    }
+
+    /// Iterate over the values of this grid in row-major order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.0.iter()
+    }
+
+    /// Iterate over the points and values of this grid in row-major order.
+    pub fn cells(&self) -> impl '_ + Iterator<Item = ((usize, usize), &T)> {
+        Self::points().zip(&self.0)
+    }
+
+    /// The values of row `y`, if it is in bounds
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < HEIGHT).then(|| &self.0[y * WIDTH..(y + 1) * WIDTH])
+    }
+
+    /// The values of column `x` from top to bottom, if it is in bounds
+    pub fn column(&self, x: usize) -> Option<impl '_ + Iterator<Item = &T>> {
+        (x < WIDTH).then(|| self.0.iter().skip(x).step_by(WIDTH))
+    }
+
+    /// Iterate over the points and values which are members of an active set, in row-major order.
+    pub fn line(&self, active: Active) -> impl '_ + Iterator<Item = ((usize, usize), &T)> {
+        Self::line_points(active).map(|point| (point, &self[point]))
+    }
+
+    /// Construct a grid of the same dimensions by applying `f` to each value.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U, WIDTH, HEIGHT> {
+        Grid(self.0.iter().map(f).collect())
+    }
+
+    /// Swap the rows and columns of this grid.
+    pub fn transpose(self) -> Grid<T, HEIGHT, WIDTH> {
+        let mut values: Vec<_> = self.0.into_iter().map(Some).collect();
+        Grid::from_fn(|x, y| {
+            values[x * WIDTH + y]
+                .take()
+                .expect("each value is moved exactly once")
+        })
+    }
+
     pub fn get(&self, x: usize, y: usize) -> Option<&T> {
         Self::idx(x, y).map(|idx| &self.0[idx])
     }
@@ -31,6 +114,60 @@ impl<T, const WIDTH: usize, const HEIGHT: usize> Grid<T, WIDTH, HEIGHT> {
     }
 }
 
+impl<T, const WIDTH: usize, const HEIGHT: usize> TryFrom<Vec<Vec<T>>> for Grid<T, WIDTH, HEIGHT> {
+    type Error = Error;
+
+    /// Construct a grid from a vector of rows.
+    fn try_from(rows: Vec<Vec<T>>) -> Result<Self, Self::Error> {
+        if rows.len() != HEIGHT {
+            return Err(Error::WrongHeight {
+                expected: HEIGHT,
+                actual: rows.len(),
+            });
+        }
+        if let Some((row, values)) = rows
+            .iter()
+            .enumerate()
+            .find(|(_, values)| values.len() != WIDTH)
+        {
+            return Err(Error::Ragged {
+                row,
+                expected: WIDTH,
+                actual: values.len(),
+            });
+        }
+        Ok(Grid(rows.into_iter().flatten().collect()))
+    }
+}
+
+impl<T, const WIDTH: usize, const HEIGHT: usize> From<Grid<T, WIDTH, HEIGHT>> for Vec<Vec<T>> {
+    fn from(grid: Grid<T, WIDTH, HEIGHT>) -> Self {
+        let mut values = grid.0.into_iter();
+        (0..HEIGHT)
+            .map(|_| values.by_ref().take(WIDTH).collect())
+            .collect()
+    }
+}
+
+impl<T, const WIDTH: usize, const HEIGHT: usize> From<[[T; WIDTH]; HEIGHT]>
+    for Grid<T, WIDTH, HEIGHT>
+{
+    fn from(rows: [[T; WIDTH]; HEIGHT]) -> Self {
+        Grid(rows.into_iter().flatten().collect())
+    }
+}
+
+impl<T, const WIDTH: usize, const HEIGHT: usize> From<Grid<T, WIDTH, HEIGHT>>
+    for [[T; WIDTH]; HEIGHT]
+{
+    fn from(grid: Grid<T, WIDTH, HEIGHT>) -> Self {
+        let mut values = grid.0.into_iter();
+        std::array::from_fn(|_| {
+            std::array::from_fn(|_| values.next().expect("grid holds WIDTH * HEIGHT values"))
+        })
+    }
+}
+
 impl<T, const WIDTH: usize, const HEIGHT: usize> Index<(usize, usize)> for Grid<T, WIDTH, HEIGHT> {
     type Output = T;
 
@@ -46,3 +183,74 @@ impl<T, const WIDTH: usize, const HEIGHT: usize> IndexMut<(usize, usize)>
         self.get_mut(x, y).unwrap()
     }
 }
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("expected {expected} rows but got {actual}")]
+    WrongHeight { expected: usize, actual: usize },
+    #[error("row {row} has {actual} values but rows must have {expected}")]
+    Ragged {
+        row: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_grid() -> Grid<u8, 3, 2> {
+        Grid::from([[0, 1, 2], [3, 4, 5]])
+    }
+
+    #[test]
+    fn views() {
+        let grid = make_grid();
+        assert_eq!(grid.row(1), Some(&[3, 4, 5][..]));
+        assert!(grid.row(2).is_none());
+        assert_eq!(grid.column(2).unwrap().collect::<Vec<_>>(), [&2, &5]);
+        assert!(grid.column(3).is_none());
+        assert_eq!(grid.cells().nth(4), Some(((1, 1), &4)));
+        let line: Vec<_> = grid.line(Active::Diagonal(1)).collect();
+        assert_eq!(line, [((1, 0), &1), ((2, 1), &5)]);
+        assert_eq!(grid.line(Active::Column(0)).count(), 2);
+    }
+
+    #[test]
+    fn transformations() {
+        let grid = make_grid();
+        let doubled = grid.map(|value| value * 2);
+        assert_eq!(doubled[(2, 1)], 10);
+
+        let transposed = grid.clone().transpose();
+        assert_eq!(<[[u8; 2]; 3]>::from(transposed), [[0, 3], [1, 4], [2, 5]]);
+        assert_eq!(Grid::<_, 3, 2>::from_fn(|x, y| x + y)[(2, 1)], 3);
+        assert_eq!(
+            Vec::<Vec<u8>>::from(grid),
+            vec![vec![0, 1, 2], vec![3, 4, 5]]
+        );
+    }
+
+    #[test]
+    fn from_rows() {
+        let grid = Grid::<_, 2, 2>::try_from(vec![vec!['a', 'b'], vec!['c', 'd']]).unwrap();
+        assert_eq!(grid[(0, 1)], 'c');
+        assert!(matches!(
+            Grid::<_, 2, 2>::try_from(vec![vec!['a', 'b'], vec!['c']]),
+            Err(Error::Ragged {
+                row: 1,
+                expected: 2,
+                actual: 1
+            })
+        ));
+        assert!(matches!(
+            Grid::<_, 2, 2>::try_from(vec![vec!['a', 'b']]),
+            Err(Error::WrongHeight {
+                expected: 2,
+                actual: 1
+            })
+        ));
+    }
+}
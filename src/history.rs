@@ -0,0 +1,197 @@
+use crate::{
+    matrix::{Error, Matrix},
+    rules::{Rules, Standard},
+};
+
+/// A single move in the history tree.
+#[derive(Debug, Clone)]
+struct Node {
+    point: (usize, usize),
+    parent: usize,
+    children: Vec<usize>,
+    /// Index into `children` of the branch which `redo` follows.
+    preferred: usize,
+}
+
+/// Tracks the moves made on a [`Matrix`], supporting undo and redo.
+///
+/// Undoing a move does not forget it. If a different move is made afterwards, the undone move
+/// remains available as an alternative branch: [`branches`][Self::branches] lists the moves
+/// which can be redone from the current state, and [`select`][Self::select]ing any of them
+/// returns to that branch. [`redo`][Self::redo] follows the most recently visited branch.
+///
+/// The solver works on a bare [`Matrix`] with [`deselect`][Matrix::deselect], which doesn't
+/// retain any history; this type is for interactive play.
+#[derive(Debug, Clone)]
+pub struct History<T, const WIDTH: usize, const HEIGHT: usize, R = Standard> {
+    matrix: Matrix<T, WIDTH, HEIGHT, R>,
+    // Node 0 is the root, representing the state of the matrix when the history began.
+    // Its `point` is meaningless.
+    nodes: Vec<Node>,
+    current: usize,
+}
+
+impl<T, const WIDTH: usize, const HEIGHT: usize, R> History<T, WIDTH, HEIGHT, R>
+where
+    T: Copy,
+    R: Rules,
+{
+    /// Begin tracking history on this matrix.
+    ///
+    /// Selections already made on the matrix are not part of the history and can't be undone.
+    pub fn new(matrix: Matrix<T, WIDTH, HEIGHT, R>) -> Self {
+        Self {
+            matrix,
+            nodes: vec![Node {
+                point: (0, 0),
+                parent: 0,
+                children: Vec::new(),
+                preferred: 0,
+            }],
+            current: 0,
+        }
+    }
+
+    /// The matrix in its current state
+    pub fn matrix(&self) -> &Matrix<T, WIDTH, HEIGHT, R> {
+        &self.matrix
+    }
+
+    /// Stop tracking history, returning the matrix in its current state.
+    pub fn into_matrix(self) -> Matrix<T, WIDTH, HEIGHT, R> {
+        self.matrix
+    }
+
+    /// Select the point at the given coordinates if it is legal to do so.
+    ///
+    /// If this point was previously selected from the current state and then undone,
+    /// this returns to that branch of the history. Otherwise it starts a new branch.
+    pub fn select(&mut self, x: usize, y: usize) -> Result<T, Error> {
+        let value = self.matrix.select(x, y)?;
+
+        let existing = self.nodes[self.current]
+            .children
+            .iter()
+            .position(|&child| self.nodes[child].point == (x, y));
+        let branch = match existing {
+            Some(branch) => branch,
+            None => {
+                let child = self.nodes.len();
+                self.nodes.push(Node {
+                    point: (x, y),
+                    parent: self.current,
+                    children: Vec::new(),
+                    preferred: 0,
+                });
+                let children = &mut self.nodes[self.current].children;
+                children.push(child);
+                children.len() - 1
+            }
+        };
+
+        let node = &mut self.nodes[self.current];
+        node.preferred = branch;
+        self.current = node.children[branch];
+        Ok(value)
+    }
+
+    /// Undo the most recent move.
+    ///
+    /// Return the point which was deselected, or `None` if there is nothing to undo.
+    pub fn undo(&mut self) -> Option<(usize, usize)> {
+        if self.current == 0 {
+            return None;
+        }
+        let point = self.matrix.deselect();
+        debug_assert_eq!(point, Some(self.nodes[self.current].point));
+        self.current = self.nodes[self.current].parent;
+        point
+    }
+
+    /// Redo the most recently undone move from the current state.
+    ///
+    /// Return the point which was reselected, or `None` if there is nothing to redo.
+    pub fn redo(&mut self) -> Option<(usize, usize)> {
+        let node = &self.nodes[self.current];
+        let child = *node.children.get(node.preferred)?;
+        let (x, y) = self.nodes[child].point;
+        self.matrix
+            .select(x, y)
+            .expect("recorded move remains legal from its parent state");
+        self.current = child;
+        Some((x, y))
+    }
+
+    /// `true` when there is a move to undo
+    pub fn can_undo(&self) -> bool {
+        self.current != 0
+    }
+
+    /// `true` when there is a move to redo
+    pub fn can_redo(&self) -> bool {
+        !self.nodes[self.current].children.is_empty()
+    }
+
+    /// Iterate over the moves which can be redone from the current state.
+    ///
+    /// Each of these can be returned to by [`select`][Self::select]ing it.
+    pub fn branches(&self) -> impl '_ + Iterator<Item = (usize, usize)> {
+        self.nodes[self.current]
+            .children
+            .iter()
+            .map(|&child| self.nodes[child].point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interner::{InternedString, Interner},
+        matrix::Active,
+        test_support::{make_interner, make_matrix},
+    };
+
+    fn make_history(interner: &Interner<String>) -> History<InternedString<'_>, 3, 3> {
+        History::new(make_matrix(interner))
+    }
+
+    #[test]
+    fn undo_redo_round_trip() {
+        let interner = make_interner();
+        let mut history = make_history(&interner);
+        history.select(1, 0).unwrap();
+        history.select(1, 2).unwrap();
+        let snapshot = history.matrix().snapshot();
+
+        assert_eq!(history.undo(), Some((1, 2)));
+        assert_eq!(history.undo(), Some((1, 0)));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.matrix().active(), Active::Row(0));
+
+        assert_eq!(history.redo(), Some((1, 0)));
+        assert_eq!(history.redo(), Some((1, 2)));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.matrix().snapshot(), snapshot);
+    }
+
+    #[test]
+    fn branches_are_retained() {
+        let interner = make_interner();
+        let mut history = make_history(&interner);
+        history.select(0, 0).unwrap();
+        history.undo();
+        history.select(2, 0).unwrap();
+        history.undo();
+
+        assert_eq!(history.branches().collect::<Vec<_>>(), [(0, 0), (2, 0)]);
+        // redo follows the most recent branch
+        assert_eq!(history.redo(), Some((2, 0)));
+        history.undo();
+        // selecting an existing branch returns to it rather than duplicating it
+        history.select(0, 0).unwrap();
+        history.undo();
+        assert_eq!(history.branches().count(), 2);
+        assert_eq!(history.redo(), Some((0, 0)));
+    }
+}
@@ -1,4 +1,9 @@
-use std::{borrow::Borrow, fmt, ops::Deref};
+use std::{
+    borrow::Borrow,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
 
 /// An interner keeps track of several possibly-non-`Copy` values, and can
 /// produce [`Interned`] versions of those values which implement `Copy`.
@@ -24,13 +29,76 @@ impl<T> Interner<T> {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Get the interned version of a symbol, if it is in range for this interner.
+    ///
+    /// See [`Symbol`] for when symbols remain valid.
+    pub fn get_symbol(&self, symbol: Symbol) -> Option<Interned<'_, T>> {
+        self.get_index(symbol.0 as usize)
+    }
+
+    /// Get the value of a symbol, if it is in range for this interner.
+    ///
+    /// See [`Symbol`] for when symbols remain valid.
+    pub fn resolve(&self, symbol: Symbol) -> Option<&T> {
+        self.0.get(symbol.0 as usize)
+    }
+
+    /// The number of distinct values in this interner
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get the interned value at the given index, if it is in range.
+    ///
+    /// Values are indexed in sorted order. As with [`Symbol`]s, inserting values can change
+    /// the index of existing values.
+    pub fn get_index(&self, idx: usize) -> Option<Interned<'_, T>> {
+        (idx < self.0.len()).then_some(Interned {
+            interner: self,
+            idx,
+        })
+    }
+
+    pub(crate) fn value(&self, idx: usize) -> Option<&T> {
+        self.0.get(idx)
+    }
+
+    /// Iterate over the values of this interner, in sorted order.
+    pub fn iter(
+        &self,
+    ) -> impl '_ + DoubleEndedIterator<Item = Interned<'_, T>> + ExactSizeIterator {
+        (0..self.0.len()).map(move |idx| Interned {
+            interner: self,
+            idx,
+        })
+    }
+
+    /// Retain only the values for which `keep` returns `true`.
+    ///
+    /// Like all modifications, this requires that no [`Interned`] handles are alive, which the
+    /// borrow checker enforces. [`Symbol`]s produced before this call are invalidated.
+    pub fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        self.0.retain(keep);
+    }
+
+    /// Remove all values from this interner.
+    ///
+    /// As with [`retain`][Self::retain], no handles may be alive.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
 }
 
 impl<T> Interner<T>
 where
     T: Ord,
 {
-    fn interned(&self, idx: usize) -> Interned<T> {
+    fn interned(&self, idx: usize) -> Interned<'_, T> {
         Interned {
             interner: self,
             idx,
@@ -38,7 +106,7 @@ where
     }
 
     /// Get the interned version of the provided value, if it is available in the interner.
-    pub fn get<Q>(&self, value: &Q) -> Option<Interned<T>>
+    pub fn get<Q>(&self, value: &Q) -> Option<Interned<'_, T>>
     where
         T: Borrow<Q>,
         Q: Ord + ?Sized,
@@ -49,10 +117,19 @@ where
             .map(|idx| self.interned(idx))
     }
 
+    /// `true` if the provided value is available in the interner.
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(value).is_some()
+    }
+
     /// Insert the provided value into this interner.
     ///
     /// If there are many values to insert, [`extend`][self.extend] will likely be more efficient.
-    pub fn insert(&mut self, value: T) -> Interned<T> {
+    pub fn insert(&mut self, value: T) -> Interned<'_, T> {
         match self.0.binary_search(&value) {
             Ok(idx) => self.interned(idx),
             Err(idx) => {
@@ -78,8 +155,9 @@ where
         // this is free because `Vec::default()` doesn't allocate
         let mut left = std::mem::take(&mut self.0).into_iter().peekable();
 
-        // sort the input values, then turn them into an iterator
+        // sort and deduplicate the input values, then turn them into an iterator
         values.sort_unstable();
+        values.dedup();
         let mut right = values.into_iter().peekable();
 
         // merge sort
@@ -111,6 +189,26 @@ where
     }
 }
 
+impl<T> Extend<T> for Interner<T>
+where
+    T: Ord,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        Interner::extend(self, values.into_iter().collect());
+    }
+}
+
+impl<T> FromIterator<T> for Interner<T>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut interner = Self::new();
+        Interner::extend(&mut interner, values.into_iter().collect());
+        interner
+    }
+}
+
 /// An interned value.
 ///
 /// Interned values always implement `Clone` and `Copy`, regardless of whether
@@ -139,6 +237,10 @@ where
 /// let a_double_prime = interner_2.get(&'a').unwrap();
 /// assert_ne!(a, a_double_prime);
 /// ```
+///
+/// Hashing is consistent with this: equal values from different interners hash differently.
+///
+/// To turn such mix-ups into compile errors, use [`Interner::brand`].
 #[derive(Eq)]
 pub struct Interned<'a, T> {
     interner: &'a Interner<T>,
@@ -149,10 +251,95 @@ impl<'a, T> Interned<'a, T> {
     fn interner_equal(&self, other: &Self) -> bool {
         std::ptr::eq(self.interner, other.interner)
     }
+
+    pub(crate) fn interner(&self) -> &'a Interner<T> {
+        self.interner
+    }
+
+    /// The index of this value within its interner. See [`Interner::get_index`].
+    pub fn index(&self) -> usize {
+        self.idx
+    }
+
+    /// Compare two values by their position in the interner.
+    ///
+    /// This is a total order for values from a single interner, matching the order of the
+    /// values themselves, so it can be used with `sort_by` where the [`PartialOrd`] impl
+    /// can't. It is meaningless for values from different interners.
+    ///
+    /// ```rust
+    /// # use breach_protocol::Interner;
+    /// let interner: Interner<_> = ["E9", "1C", "55"].into_iter().collect();
+    /// let mut values: Vec<_> = ["55", "E9", "1C"]
+    ///     .iter()
+    ///     .map(|value| interner.get(value).unwrap())
+    ///     .collect();
+    /// values.sort_by(|a, b| a.cmp_index(b));
+    /// assert_eq!(values.iter().map(|value| **value).collect::<Vec<_>>(), ["1C", "55", "E9"]);
+    /// ```
+    ///
+    /// [Branded][Interner::brand] values implement `Ord` directly.
+    pub fn cmp_index(&self, other: &Self) -> std::cmp::Ordering {
+        self.idx.cmp(&other.idx)
+    }
+
+    /// A handle to this value which doesn't borrow the interner.
+    pub fn symbol(&self) -> Symbol {
+        Symbol(u32::try_from(self.idx).expect("interner holds fewer than 2^32 values"))
+    }
+}
+
+/// A handle to an interned value which doesn't borrow its interner.
+///
+/// Symbols are resolved through the interner which produced them, with
+/// [`Interner::resolve`] or [`Interner::get_symbol`]. Inserting values into an interner
+/// can renumber its values, so a symbol is only meaningful for the interner which produced
+/// it, until that interner is next modified.
+///
+/// Symbols produced by a [`StableInterner`][crate::StableInterner] are never renumbered, so
+/// their [ids][Symbol::id] can be persisted and [restored][Symbol::from_id].
+///
+/// Unlike [`Interned`], symbols carry no reference to their interner, so they can't detect
+/// being resolved through the wrong one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(pub(crate) u32);
+
+impl Symbol {
+    /// The numeric id of this symbol.
+    pub fn id(self) -> u32 {
+        self.0
+    }
+
+    /// Recreate a symbol from its numeric id.
+    pub fn from_id(id: u32) -> Self {
+        Self(id)
+    }
 }
 
 pub type InternedString<'a> = Interned<'a, String>;
 
+/// A source of tokens, looked up by their textual form.
+///
+/// [`Matrix::new`][crate::Matrix::new] and [`Sequence::new`][crate::Sequence::new] accept any
+/// lookup, and produce matrices and sequences of its tokens. Looking up through an
+/// `&Interner<String>` produces [`InternedString`]s; through a
+/// [`BrandedInterner`][crate::BrandedInterner], branded handles, so that a matrix and sequence
+/// built from different interners can't be solved together.
+pub trait Lookup: Copy {
+    type Token: Copy;
+
+    /// The token for `item`, if there is one.
+    fn lookup(self, item: &str) -> Option<Self::Token>;
+}
+
+impl<'a> Lookup for &'a Interner<String> {
+    type Token = InternedString<'a>;
+
+    fn lookup(self, item: &str) -> Option<Self::Token> {
+        self.get(item)
+    }
+}
+
 impl<'a, T> fmt::Debug for Interned<'a, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Interned").field("idx", &self.idx).finish()
@@ -173,6 +360,14 @@ impl<'a, T> PartialEq for Interned<'a, T> {
     }
 }
 
+// Hashing must agree with equality, so it takes the identity of the interner into account.
+impl<'a, T> Hash for Interned<'a, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self.interner, state);
+        self.idx.hash(state);
+    }
+}
+
 // Interned values can be ordered, but can't implement `Ord` because in the event
 // that the interners don't match, there simply is no ordering between them.
 impl<'a, T> PartialOrd for Interned<'a, T>
@@ -188,10 +383,7 @@ where
 // (That's the point.)
 impl<'a, T> Clone for Interned<'a, T> {
     fn clone(&self) -> Self {
-        Self {
-            interner: self.interner,
-            idx: self.idx,
-        }
+        *self
     }
 }
 
@@ -206,3 +398,47 @@ where
         self.deref().fmt(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collection_api() {
+        let mut interner: Interner<_> = ["FF", "1C", "E9", "1C"].into_iter().collect();
+        assert_eq!(interner.len(), 3);
+        assert!(interner.contains("E9"));
+        assert!(!interner.contains("55"));
+
+        let values: Vec<_> = interner.iter().map(|value| *value).collect();
+        assert_eq!(values, ["1C", "E9", "FF"]);
+        let e9 = interner.get_index(1).unwrap();
+        assert_eq!((*e9, e9.index()), ("E9", 1));
+        assert!(interner.get_index(3).is_none());
+
+        Extend::extend(&mut interner, ["55"]);
+        interner.retain(|value| value.starts_with(char::is_numeric));
+        let values: Vec<_> = interner.iter().map(|value| *value).collect();
+        assert_eq!(values, ["1C", "55"]);
+
+        interner.clear();
+        assert!(interner.is_empty());
+    }
+
+    #[test]
+    fn hash_matches_equality() {
+        use std::collections::HashSet;
+
+        let interner: Interner<_> = ["1C", "55"].into_iter().collect();
+        let other = interner.clone();
+        let set: HashSet<_> = ["1C", "55", "1C"]
+            .iter()
+            .map(|value| interner.get(value).unwrap())
+            .chain(other.get("1C"))
+            .collect();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&interner.get("1C").unwrap()));
+        assert!(set.contains(&other.get("1C").unwrap()));
+        assert!(!set.contains(&other.get("55").unwrap()));
+    }
+}
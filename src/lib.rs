@@ -1,12 +1,50 @@
+mod branded;
+mod buffer_size;
+mod code;
+mod error;
+pub mod ffi;
 mod grid;
+mod history;
 mod interner;
+mod lint;
 mod matrix;
+mod owned;
+mod puzzle;
+#[cfg(feature = "python")]
+mod python;
+mod replay;
+mod rules;
 mod sequence;
+#[cfg(feature = "serve")]
+mod serve;
+mod shared_interner;
+mod stable_interner;
+mod svg;
+mod terminal;
+#[cfg(test)]
+pub(crate) mod test_support;
+mod validate;
 
-pub use grid::Grid;
-pub use interner::{Interned, InternedString, Interner};
-pub use matrix::{Active, Matrix};
-pub use sequence::Sequence;
+pub use branded::{BrandedInterned, BrandedInterner};
+pub use buffer_size::{BufferSizes, Error as BufferSizeError};
+pub use code::{Code, Error as CodeError};
+pub use error::Error;
+pub use grid::{Error as GridError, Grid};
+pub use history::History;
+pub use interner::{Interned, InternedString, Interner, Lookup, Symbol};
+pub use lint::{lint, lint_with_origins, Issue, Origin, Severity};
+pub use matrix::{Active, Error as MatrixError, Matrix, Snapshot};
+pub use puzzle::Puzzle;
+pub use replay::{Error as ReplayError, Replay};
+pub use rules::{Free, Rules, Standard};
+pub use sequence::{Error as SequenceError, Pattern, Sequence};
+#[cfg(feature = "serve")]
+pub use serve::Service;
+pub use shared_interner::{Shared, SharedInterner};
+pub use stable_interner::StableInterner;
+pub use svg::render_svg;
+pub use terminal::{render_terminal, Style};
+pub use validate::{validate, Error as ValidationError, Validation};
 
 /// The Breach Protocol minigame.
 ///
@@ -36,68 +74,309 @@ pub use sequence::Sequence;
 ///
 /// ## Implementation Notes
 ///
-/// As Rust doesn't like self-referential structs, and the internals are all built around interned
-/// strings which borrow from a central interner, this struct can't encapsulate both the interner
-/// and also the interned values. We choose to keep the interner; external code can own the subordinate
-/// structures.
+/// Matrices and sequences may hold any `Copy + Eq` token. Interned strings borrow from an
+/// [`Interner`], which as Rust doesn't like self-referential structs must be owned outside of
+/// both them and this struct. To keep an interner together with its values, use [`Puzzle`].
 ///
 /// While the game can be challenging for humans, it is sharply bounded in scale. Exhaustive search
 /// should easily be fast enough.
+#[derive(Debug, Clone)]
 pub struct BreachProtocol {
-    interner: Interner<String>,
     buffer_size: usize,
 }
 
 impl BreachProtocol {
-    pub fn solve<'a, 'interner, const WIDTH: usize, const HEIGHT: usize>(
-        &'interner self,
-        matrix: &'a mut Matrix<'a, WIDTH, HEIGHT>,
-        sequences: &[Sequence<'a>],
-    ) -> Vec<Solution<'a>>
+    pub fn new(buffer_size: usize) -> Self {
+        Self { buffer_size }
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Find all solutions which complete at least one sequence.
+    ///
+    /// Tokens may be of any `Copy + Eq` type, such as [`Code`]s or interned strings.
+    ///
+    /// The search follows the [`Rules`] of the matrix, from its current state. Any selections
+    /// already made are included in each solution; use [`resume`][Self::resume] to get only
+    /// the remaining moves. A matrix with more selections than fit in the buffer has no
+    /// solutions.
+    pub fn solve<T, R, const WIDTH: usize, const HEIGHT: usize>(
+        &self,
+        matrix: &mut Matrix<T, WIDTH, HEIGHT, R>,
+        sequences: &[Sequence<T>],
+    ) -> Vec<Solution<T>>
     where
-        'interner: 'a,
+        T: Copy + Eq,
+        R: Rules,
     {
         let mut solutions = Vec::new();
-        self.solve_inner(matrix, sequences, &mut solutions);
+        self.solve_inner(matrix, &mut |matrix| {
+            if matrix.selected_len() == self.buffer_size {
+                // only compute which sequences were matched once the buffer is full
+                let solution = Solution::new(matrix, sequences);
+                if !solution.matches.is_empty() {
+                    solutions.push(solution);
+                }
+            }
+            true
+        });
         solutions
     }
 
-    fn solve_inner<'a, 'interner, const WIDTH: usize, const HEIGHT: usize>(
-        &'interner self,
-        matrix: &'a mut Matrix<'a, WIDTH, HEIGHT>,
-        sequences: &[Sequence<'a>],
-        solutions: &mut Vec<Solution<'a>>,
+    /// Find all ways to finish a game from the current state of the matrix.
+    ///
+    /// The selections already made count toward the buffer size and toward completing sequences,
+    /// so the matches of each solution are those of the whole buffer. Each solution's path and
+    /// buffer hold only the remaining moves, which may be none if the buffer is already full;
+    /// [`played`][Solution::played] counts the selections which preceded them. If the matrix
+    /// already holds more selections than fit in the buffer, there are no solutions.
+    ///
+    /// ```rust
+    /// # use breach_protocol::{BreachProtocol, Code, Matrix, Sequence};
+    /// let codes = Code::parse_list("1C 55 BD 55 BD 1C BD 1C 55").unwrap();
+    /// let mut matrix = Matrix::<_, 3, 3>::from_values(codes).unwrap();
+    /// let sequences = [Sequence::from_values(Code::parse_list("55 1C").unwrap())];
+    ///
+    /// matrix.select(1, 0).unwrap();
+    /// let solutions = BreachProtocol::new(2).resume(&mut matrix, &sequences);
+    /// assert_eq!(solutions[0].path(), [(1, 2)]);
+    /// assert_eq!(solutions[0].played(), 1);
+    /// assert_eq!(solutions[0].matches(), [0]);
+    /// assert_eq!(matrix.selections(), [(1, 0)]);
+    /// ```
+    pub fn resume<T, R, const WIDTH: usize, const HEIGHT: usize>(
+        &self,
+        matrix: &mut Matrix<T, WIDTH, HEIGHT, R>,
+        sequences: &[Sequence<T>],
+    ) -> Vec<Solution<T>>
+    where
+        T: Copy + Eq,
+        R: Rules,
+    {
+        let played = matrix.selected_len();
+        let mut solutions = self.solve(matrix, sequences);
+        for solution in &mut solutions {
+            solution.path.drain(..played);
+            solution.buffer.drain(..played);
+            solution.played = played;
+        }
+        solutions
+    }
+
+    /// Find the smallest buffer sizes which complete each subset of the sequences.
+    ///
+    /// Buffers up to this protocol's buffer size are considered, so it acts as the limit of the
+    /// search. One search covers every size and subset, and follows the [`Rules`] of the matrix
+    /// from its current state, restoring it afterwards.
+    ///
+    /// Fails if there are more than 64 sequences.
+    pub fn buffer_sizes<T, R, const WIDTH: usize, const HEIGHT: usize>(
+        &self,
+        matrix: &mut Matrix<T, WIDTH, HEIGHT, R>,
+        sequences: &[Sequence<T>],
+    ) -> Result<BufferSizes, BufferSizeError>
+    where
+        T: Copy + Eq,
+        R: Rules,
+    {
+        buffer_size::search(self, matrix, sequences)
+    }
+
+    /// Visit every state reachable from the matrix whose buffer fits in the buffer size, depth
+    /// first, restoring the matrix afterwards.
+    ///
+    /// The moves following a state are only explored if `visit` returns `true` for it.
+    fn solve_inner<T, R, F, const WIDTH: usize, const HEIGHT: usize>(
+        &self,
+        matrix: &mut Matrix<T, WIDTH, HEIGHT, R>,
+        visit: &mut F,
     ) where
-        'interner: 'a,
+        T: Copy + Eq,
+        R: Rules,
+        F: FnMut(&Matrix<T, WIDTH, HEIGHT, R>) -> bool,
     {
         let depth = matrix.selected_len();
-        if depth < self.buffer_size {
-            for (x, y) in matrix.legal_selections() {
-                matrix.select(x, y).expect("legal selection remained legal");
-                self.solve_inner(matrix, sequences, solutions);
-                matrix.deselect();
-            }
-        } else {
-            // only compute which sequences were matched once the buffer is full
-            let matches: Vec<_> = sequences
-                .iter()
-                .enumerate()
-                .filter_map(|(idx, sequence)| {
-                    sequence.is_matched(matrix.selected_values()).then_some(idx)
-                })
-                .collect();
-
-            if !matches.is_empty() {
-                solutions.push(Solution {
-                    buffer: matrix.selected_values().collect(),
-                    matches,
-                });
+        if depth > self.buffer_size || !visit(matrix) || depth == self.buffer_size {
+            return;
+        }
+        for (x, y) in matrix.legal_selections() {
+            if matrix.is_chosen(x, y) {
+                continue;
             }
+            matrix.select(x, y).expect("legal selection remained legal");
+            self.solve_inner(matrix, visit);
+            matrix.deselect();
         }
     }
 }
 
-pub struct Solution<'a> {
-    buffer: Vec<InternedString<'a>>,
+#[derive(Debug, Clone)]
+pub struct Solution<T> {
+    path: Vec<(usize, usize)>,
+    buffer: Vec<T>,
     matches: Vec<usize>,
+    played: usize,
+}
+
+impl<T> Solution<T>
+where
+    T: Copy + Eq,
+{
+    /// Record the current selections of the matrix, and which sequences they complete.
+    pub(crate) fn new<R: Rules, const WIDTH: usize, const HEIGHT: usize>(
+        matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+        sequences: &[Sequence<T>],
+    ) -> Self {
+        let buffer: Vec<T> = matrix.selected_values().collect();
+        let matches = sequences
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, sequence)| sequence.is_matched(&buffer).then_some(idx))
+            .collect();
+
+        Solution {
+            path: matrix.selections().to_vec(),
+            buffer,
+            matches,
+            played: 0,
+        }
+    }
+}
+
+impl<T> Solution<T> {
+    /// The points selected, in order
+    pub fn path(&self) -> &[(usize, usize)] {
+        &self.path
+    }
+
+    /// The values selected into the buffer, in order
+    pub fn buffer(&self) -> &[T] {
+        &self.buffer
+    }
+
+    /// The indices of the sequences which this solution completes
+    pub fn matches(&self) -> &[usize] {
+        &self.matches
+    }
+
+    /// The number of selections which had already been made when this solution was found by
+    /// [`resume`][BreachProtocol::resume], and which precede its path; zero otherwise.
+    pub fn played(&self) -> usize {
+        self.played
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{make_interner, make_matrix};
+
+    #[test]
+    fn solve() {
+        let protocol = BreachProtocol::new(3);
+        let interner = &make_interner();
+        let mut matrix = make_matrix(interner);
+        let sequences = [
+            Sequence::new(interner, ["2B", "1A"]).unwrap(),
+            Sequence::new(interner, ["1A", "3C", "3C"]).unwrap(),
+        ];
+
+        let solutions = protocol.solve(&mut matrix, &sequences);
+        assert!(!solutions.is_empty());
+        assert!(solutions.iter().any(
+            |solution| solution.path() == [(1, 0), (1, 2), (0, 2)] && solution.matches() == [0]
+        ));
+        for solution in &solutions {
+            assert_eq!(solution.path().len(), 3);
+            assert!(validate(&matrix, &sequences, 3, solution.path()).is_valid());
+        }
+        // the matrix is restored once solving completes
+        assert_eq!(matrix.selected_len(), 0);
+        assert_eq!(matrix.active(), Active::Row(0));
+    }
+
+    #[test]
+    fn resume() {
+        let protocol = BreachProtocol::new(3);
+        let interner = &make_interner();
+        let mut matrix = make_matrix(interner);
+        let sequences = [Sequence::new(interner, ["2B", "1A"]).unwrap()];
+
+        // the first half of the sequence is already in the buffer
+        matrix.select(1, 0).unwrap();
+        let solutions = protocol.resume(&mut matrix, &sequences);
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            assert_eq!(solution.played(), 1);
+            assert_eq!(solution.path().len(), 2);
+            assert_eq!(solution.path()[0], (1, 2));
+            assert_eq!(solution.buffer().len(), 2);
+            assert_eq!(solution.matches(), [0]);
+            // the moves already played, then the remaining ones, make a valid game
+            let path = [matrix.selections(), solution.path()].concat();
+            assert!(validate(&matrix, &sequences, 3, &path).is_valid());
+        }
+        assert_eq!(matrix.selections(), [(1, 0)]);
+
+        // a full buffer has nothing left to play, but still counts its matches
+        matrix.select(1, 2).unwrap();
+        matrix.select(0, 2).unwrap();
+        let solutions = protocol.resume(&mut matrix, &sequences);
+        assert_eq!(solutions.len(), 1);
+        assert!(solutions[0].path().is_empty());
+        assert_eq!(solutions[0].played(), 3);
+        assert_eq!(solutions[0].matches(), [0]);
+
+        // an overfull buffer has no solutions at all
+        matrix.select(0, 0).unwrap();
+        assert!(protocol.resume(&mut matrix, &sequences).is_empty());
+        assert!(protocol.solve(&mut matrix, &sequences).is_empty());
+        assert_eq!(matrix.selected_len(), 4);
+    }
+
+    #[test]
+    fn solve_custom_tokens() {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Glyph {
+            Circle,
+            Square,
+            Cross,
+        }
+        use Glyph::*;
+
+        let mut matrix = Matrix::<_, 3, 3>::from_values([
+            Circle, Square, Cross, Square, Cross, Circle, Cross, Circle, Square,
+        ])
+        .unwrap();
+        let sequences = [Sequence::from_values([Square, Circle])];
+        let solutions = BreachProtocol::new(3).solve(&mut matrix, &sequences);
+        assert!(solutions
+            .iter()
+            .any(|solution| solution.path() == [(1, 0), (1, 2), (0, 2)]));
+        for solution in &solutions {
+            let validation = validate(&matrix, &sequences, 3, solution.path());
+            assert_eq!(validation.solution().buffer(), solution.buffer());
+        }
+
+        // wildcards match whatever is selected in their place
+        let sequences = [Sequence::from_patterns([
+            Pattern::Exact(Square),
+            Pattern::Any,
+            Pattern::OneOf(vec![Circle, Cross]),
+        ])];
+        let solutions = BreachProtocol::new(3).solve(&mut matrix, &sequences);
+        assert!(solutions
+            .iter()
+            .any(|solution| solution.path() == [(1, 0), (1, 1), (2, 1)]));
+        for solution in &solutions {
+            assert_eq!(solution.buffer()[0], Square);
+            assert_ne!(solution.buffer()[2], Square);
+        }
+
+        // chars display, so they can also be rendered
+        let matrix = Matrix::<_, 2, 2>::from_values("abba".chars()).unwrap();
+        assert_eq!(matrix.to_string(), "    0 1\n0>  a b\n1   b a\n");
+    }
 }
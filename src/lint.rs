@@ -0,0 +1,464 @@
+use std::fmt;
+
+use crate::{
+    branded::BrandedInterned,
+    interner::Interned,
+    matrix::Matrix,
+    rules::Rules,
+    sequence::{Pattern, Sequence},
+    shared_interner::Shared,
+};
+
+/// Tokens which know where they came from.
+///
+/// Interned tokens only compare equal to tokens of the same interner, so a sequence built
+/// against a different interner than its matrix can never be completed.
+/// [`lint_with_origins`] uses this trait to report such sequences distinctly from those which
+/// merely use absent tokens. Plain values all share one origin, and can be checked with
+/// [`lint`] without implementing this trait.
+pub trait Origin: PartialEq {
+    /// `true` when both tokens come from the same source, so that comparing them is meaningful.
+    fn same_origin(&self, other: &Self) -> bool;
+}
+
+impl<T> Origin for Interned<'_, T> {
+    fn same_origin(&self, other: &Self) -> bool {
+        std::ptr::eq(self.interner(), other.interner())
+    }
+}
+
+impl<T> Origin for Shared<'_, T> {
+    fn same_origin(&self, other: &Self) -> bool {
+        std::ptr::eq(self.interner(), other.interner())
+    }
+}
+
+/// Handles of one brand always come from the same interner; the compiler rejects any others.
+impl<T> Origin for BrandedInterned<'_, '_, T> {
+    fn same_origin(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// How serious an [`Issue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The puzzle is still solvable as written, but something about it is redundant.
+    Warning,
+    /// Some sequence can never be completed.
+    Error,
+}
+
+/// A problem found by [`lint`]. Sequences are identified by their index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Issue {
+    /// The sequence has no items, so it can never be matched.
+    Empty { sequence: usize },
+    /// The sequence has more items than fit in the buffer.
+    TooLong {
+        sequence: usize,
+        len: usize,
+        buffer_size: usize,
+    },
+    /// The item at `position` matches no value in the matrix, other than in blocked cells.
+    MissingToken { sequence: usize, position: usize },
+    /// The item at `position` was built against a different interner than the matrix.
+    ///
+    /// Only reported by [`lint_with_origins`].
+    ForeignToken { sequence: usize, position: usize },
+    /// The sequence has the same items as an earlier one.
+    Duplicate { sequence: usize, original: usize },
+    /// Every buffer which completes `container` also completes this sequence.
+    Contained { sequence: usize, container: usize },
+}
+
+impl Issue {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Issue::Empty { .. }
+            | Issue::TooLong { .. }
+            | Issue::MissingToken { .. }
+            | Issue::ForeignToken { .. } => Severity::Error,
+            Issue::Duplicate { .. } | Issue::Contained { .. } => Severity::Warning,
+        }
+    }
+
+    /// The index of the sequence with the problem
+    pub fn sequence(&self) -> usize {
+        match *self {
+            Issue::Empty { sequence }
+            | Issue::TooLong { sequence, .. }
+            | Issue::MissingToken { sequence, .. }
+            | Issue::ForeignToken { sequence, .. }
+            | Issue::Duplicate { sequence, .. }
+            | Issue::Contained { sequence, .. } => sequence,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::Empty { sequence } => write!(f, "sequence {sequence} is empty"),
+            Issue::TooLong {
+                sequence,
+                len,
+                buffer_size,
+            } => write!(
+                f,
+                "sequence {sequence} has {len} items but the buffer only holds {buffer_size}"
+            ),
+            Issue::MissingToken { sequence, position } => write!(
+                f,
+                "item {position} of sequence {sequence} matches nothing selectable in the matrix"
+            ),
+            Issue::ForeignToken { sequence, position } => write!(
+                f,
+                "item {position} of sequence {sequence} comes from a different interner than the matrix"
+            ),
+            Issue::Duplicate { sequence, original } => {
+                write!(f, "sequence {sequence} duplicates sequence {original}")
+            }
+            Issue::Contained {
+                sequence,
+                container,
+            } => write!(
+                f,
+                "sequence {sequence} is completed whenever sequence {container} is"
+            ),
+        }
+    }
+}
+
+/// `true` when every token matched by `inner` is also matched by `outer`.
+fn covers<T: PartialEq>(outer: &Pattern<T>, inner: &Pattern<T>) -> bool {
+    match (outer, inner) {
+        (Pattern::Any, _) => true,
+        (_, Pattern::Any) => false,
+        (outer, inner) => inner.tokens().iter().all(|value| outer.matches(value)),
+    }
+}
+
+/// `true` when every buffer which completes `container` also completes `sequence`.
+fn contained<T: PartialEq>(sequence: &Sequence<T>, container: &Sequence<T>) -> bool {
+    let (items, outer) = (sequence.items(), container.items());
+    !items.is_empty()
+        && items.len() <= outer.len()
+        && outer
+            .windows(items.len())
+            .any(|window| items.iter().zip(window).all(|(a, b)| covers(a, b)))
+}
+
+/// Check a puzzle for problems which would otherwise only show up as missing solutions.
+///
+/// Errors are reported for sequences which can never be completed; warnings for sequences which
+/// are redundant with others. Issues are ordered by sequence. A sequence is reported as redundant
+/// at most once, against the first sequence which makes it so. Sequences which can never be
+/// completed make nothing redundant.
+///
+/// Every token is assumed to come from the same source. For interned tokens, which may not,
+/// use [`lint_with_origins`].
+///
+/// ```rust
+/// # use breach_protocol::{lint, Code, Issue, Matrix, Sequence, Severity};
+/// let matrix = Matrix::<_, 2, 2>::from_values(Code::parse_list("1C 55 55 1C").unwrap()).unwrap();
+/// let sequences = [
+///     Sequence::from_values(Code::parse_list("1C 55").unwrap()),
+///     Sequence::from_values(Code::parse_list("55").unwrap()),
+///     Sequence::from_values(Code::parse_list("E9").unwrap()),
+/// ];
+///
+/// let issues = lint(&matrix, &sequences, 2);
+/// assert_eq!(
+///     issues,
+///     [
+///         Issue::Contained { sequence: 1, container: 0 },
+///         Issue::MissingToken { sequence: 2, position: 0 },
+///     ]
+/// );
+/// assert_eq!(issues[1].severity(), Severity::Error);
+/// ```
+pub fn lint<T, R, const WIDTH: usize, const HEIGHT: usize>(
+    matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+    sequences: &[Sequence<T>],
+    buffer_size: usize,
+) -> Vec<Issue>
+where
+    T: Copy + PartialEq,
+    R: Rules,
+{
+    lint_inner(matrix, sequences, buffer_size, |_, _| true)
+}
+
+/// Check a puzzle for problems as [`lint`] does, also reporting tokens which come from a
+/// different interner than the matrix as [`Issue::ForeignToken`].
+pub fn lint_with_origins<T, R, const WIDTH: usize, const HEIGHT: usize>(
+    matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+    sequences: &[Sequence<T>],
+    buffer_size: usize,
+) -> Vec<Issue>
+where
+    T: Copy + Origin,
+    R: Rules,
+{
+    lint_inner(matrix, sequences, buffer_size, T::same_origin)
+}
+
+/// The issues which make a sequence impossible to complete.
+fn errors<T: PartialEq>(
+    idx: usize,
+    sequence: &Sequence<T>,
+    values: &[T],
+    buffer_size: usize,
+    same_origin: &impl Fn(&T, &T) -> bool,
+) -> Vec<Issue> {
+    let items = sequence.items();
+    if items.is_empty() {
+        return vec![Issue::Empty { sequence: idx }];
+    }
+
+    let mut issues = Vec::new();
+    if items.len() > buffer_size {
+        issues.push(Issue::TooLong {
+            sequence: idx,
+            len: items.len(),
+            buffer_size,
+        });
+    }
+    for (position, pattern) in items.iter().enumerate() {
+        let foreign = pattern
+            .tokens()
+            .iter()
+            .any(|token| values.iter().any(|value| !same_origin(value, token)));
+        if foreign {
+            issues.push(Issue::ForeignToken {
+                sequence: idx,
+                position,
+            });
+        } else if !values.iter().any(|value| pattern.matches(value)) {
+            issues.push(Issue::MissingToken {
+                sequence: idx,
+                position,
+            });
+        }
+    }
+    issues
+}
+
+fn lint_inner<T, R, const WIDTH: usize, const HEIGHT: usize>(
+    matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+    sequences: &[Sequence<T>],
+    buffer_size: usize,
+    same_origin: impl Fn(&T, &T) -> bool,
+) -> Vec<Issue>
+where
+    T: Copy + PartialEq,
+    R: Rules,
+{
+    // blocked cells can never be selected, so their values don't count as present
+    let values: Vec<T> = matrix
+        .values()
+        .cells()
+        .filter(|&((x, y), _)| !matrix.is_blocked(x, y))
+        .map(|(_, &value)| value)
+        .collect();
+    let errors: Vec<_> = sequences
+        .iter()
+        .enumerate()
+        .map(|(idx, sequence)| errors(idx, sequence, &values, buffer_size, &same_origin))
+        .collect();
+    let mut issues = Vec::new();
+
+    for (idx, sequence) in sequences.iter().enumerate() {
+        issues.extend_from_slice(&errors[idx]);
+        let items = sequence.items();
+        if items.is_empty() {
+            continue;
+        }
+
+        let original = sequences[..idx]
+            .iter()
+            .position(|other| other.items() == items);
+        if let Some(original) = original {
+            issues.push(Issue::Duplicate {
+                sequence: idx,
+                original,
+            });
+            continue;
+        }
+
+        let container = sequences.iter().enumerate().position(|(other, container)| {
+            // exact duplicates are reported as such, against the earlier of the two; sequences
+            // which can never be completed make nothing redundant
+            other != idx
+                && errors[other].is_empty()
+                && container.items() != items
+                && contained(sequence, container)
+        });
+        if let Some(container) = container {
+            issues.push(Issue::Contained {
+                sequence: idx,
+                container,
+            });
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{code::Code, interner::Interner, shared_interner::SharedInterner};
+
+    fn codes(s: &str) -> Sequence<Code> {
+        Sequence::from_values(Code::parse_list(s).unwrap())
+    }
+
+    #[test]
+    fn reports_each_issue() {
+        let matrix =
+            Matrix::<_, 2, 2>::from_values(Code::parse_list("1C 55 BD E9").unwrap()).unwrap();
+        let code = |s: &str| s.parse::<Code>().unwrap();
+        let sequences = [
+            codes("1C 55 BD"),
+            codes("55 BD"),
+            codes("1C 55 BD"),
+            codes("1C FF"),
+            codes(""),
+            // contains sequence 0, but is too long to make it redundant
+            codes("1C 55 BD E9"),
+            // completed by anything which completes sequence 0
+            Sequence::from_patterns([Pattern::Any, Pattern::OneOf(vec![code("55"), code("BD")])]),
+            // contained in sequence 3, which can't be completed either
+            Sequence::from_patterns([Pattern::OneOf(vec![code("FF"), code("7A")])]),
+        ];
+
+        assert_eq!(
+            lint(&matrix, &sequences, 3),
+            [
+                Issue::Contained {
+                    sequence: 1,
+                    container: 0
+                },
+                Issue::Duplicate {
+                    sequence: 2,
+                    original: 0
+                },
+                Issue::MissingToken {
+                    sequence: 3,
+                    position: 1
+                },
+                Issue::Empty { sequence: 4 },
+                Issue::TooLong {
+                    sequence: 5,
+                    len: 4,
+                    buffer_size: 3
+                },
+                Issue::Contained {
+                    sequence: 6,
+                    container: 0
+                },
+                Issue::MissingToken {
+                    sequence: 7,
+                    position: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn foreign_interner() {
+        let make_interner = || {
+            let mut interner = Interner::new();
+            interner.extend(vec!["1A".to_owned(), "2B".to_owned()]);
+            interner
+        };
+        let (interner, other) = (make_interner(), make_interner());
+        let matrix = Matrix::<_, 2, 2>::new(&interner, ["1A", "2B", "2B", "1A"]).unwrap();
+        let sequences = [
+            Sequence::new(&interner, ["1A", "2B"]).unwrap(),
+            Sequence::new(&other, ["1A", "2B"]).unwrap(),
+        ];
+
+        // without origins, foreign tokens are simply missing
+        assert!(lint(&matrix, &sequences, 2)
+            .iter()
+            .all(|issue| matches!(issue, Issue::MissingToken { sequence: 1, .. })));
+
+        let issues = lint_with_origins(&matrix, &sequences, 2);
+        assert_eq!(
+            issues,
+            [
+                Issue::ForeignToken {
+                    sequence: 1,
+                    position: 0
+                },
+                Issue::ForeignToken {
+                    sequence: 1,
+                    position: 1
+                },
+            ]
+        );
+        assert!(issues
+            .iter()
+            .all(|issue| issue.severity() == Severity::Error));
+    }
+
+    #[test]
+    fn shared_interner() {
+        let (interner, other) = (SharedInterner::new(), SharedInterner::new());
+        let values = ["1A", "2B", "2B", "1A"].map(|value| interner.insert(value.to_owned()));
+        let matrix = Matrix::<_, 2, 2>::from_values(values).unwrap();
+        let sequences = [
+            Sequence::from_values([interner.insert("2B".to_owned())]),
+            Sequence::from_values([other.insert("2B".to_owned())]),
+        ];
+
+        assert_eq!(
+            lint_with_origins(&matrix, &sequences, 2),
+            [Issue::ForeignToken {
+                sequence: 1,
+                position: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn blocked_cells() {
+        let mut matrix =
+            Matrix::<_, 2, 2>::from_values(Code::parse_list("1C 55 BD E9").unwrap()).unwrap();
+        let sequences = [codes("1C 55"), codes("BD")];
+        assert!(lint(&matrix, &sequences, 2).is_empty());
+
+        matrix.block(0, 1).unwrap();
+        assert_eq!(
+            lint(&matrix, &sequences, 2),
+            [Issue::MissingToken {
+                sequence: 1,
+                position: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn custom_tokens() {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Glyph {
+            Circle,
+            Square,
+            Cross,
+        }
+
+        let matrix = Matrix::<_, 1, 2>::from_values([Glyph::Circle, Glyph::Square]).unwrap();
+        let sequences = [Sequence::from_values([Glyph::Cross])];
+        assert_eq!(
+            lint(&matrix, &sequences, 2),
+            [Issue::MissingToken {
+                sequence: 0,
+                position: 0
+            }]
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::{env, process::ExitCode, str::FromStr, thread};
+
+use breach_protocol::Service;
+
+const USAGE: &str = "usage: breach-protocol serve [--port PORT] [--workers N] [--max-buffer N]";
+
+fn parse<T: FromStr>(value: Option<String>) -> Option<T> {
+    value?.parse().ok()
+}
+
+fn main() -> ExitCode {
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Info)
+        .parse_default_env()
+        .init();
+
+    let mut args = env::args().skip(1);
+    if args.next().as_deref() != Some("serve") {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    }
+
+    let mut port: u16 = 8077;
+    let mut workers = thread::available_parallelism().map_or(1, usize::from);
+    let mut max_buffer = Service::DEFAULT_MAX_BUFFER_SIZE;
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--port" => parse(args.next()).map(|value| port = value),
+            "--workers" => parse(args.next()).map(|value| workers = value),
+            "--max-buffer" => parse(args.next()).map(|value| max_buffer = value),
+            _ => None,
+        };
+        if parsed.is_none() {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let service = match Service::bind(("127.0.0.1", port)) {
+        Ok(service) => service.max_buffer_size(max_buffer),
+        Err(err) => {
+            log::error!("failed to bind port {port}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    log::info!("listening on http://{}", service.local_addr());
+    service.run(workers)
+}
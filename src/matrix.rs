@@ -1,12 +1,26 @@
+use std::sync::Arc;
+
 use crate::{
     grid::Grid,
-    interner::{Interned, InternedString},
+    interner::Lookup,
+    rules::{Rules, Standard},
+    sequence::make_interned,
 };
 
+/// The set of cells from which the next selection may be made.
+///
+/// Which set is active is decided by the [`Rules`] of a matrix. The shapes a set can take are
+/// fixed: rules choose among these variants, but can't define sets of their own.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Active {
     Row(usize),
     Column(usize),
+    /// The cells for which `x - y` equals the given offset
+    Diagonal(isize),
+    /// The cells for which `x + y` equals the given sum
+    AntiDiagonal(usize),
+    /// Every cell
+    Any,
 }
 
 impl Default for Active {
@@ -16,37 +30,155 @@ impl Default for Active {
 }
 
 impl Active {
+    /// `true` when the point at the given coordinates is a member of this set.
+    ///
+    /// Bounds are not considered.
+    pub fn contains(self, x: usize, y: usize) -> bool {
+        match self {
+            Active::Row(row) => y == row,
+            Active::Column(column) => x == column,
+            Active::Diagonal(offset) => x as isize - y as isize == offset,
+            Active::AntiDiagonal(sum) => x + y == sum,
+            Active::Any => true,
+        }
+    }
+
     /// Return the new active set if the specified point is valid, or `Error::NotActive` otherwise.
+    ///
+    /// This is the pivot of the [standard rules][crate::Standard]: selecting from a row makes the
+    /// selection's column active, and vice versa. Only rows and columns pivot; any other set
+    /// gives `Error::NoPivot`.
     pub fn toggle(self, x: usize, y: usize) -> Result<Self, Error> {
-        let err = Err(Error::NotActive { x, y, active: self });
+        if !self.contains(x, y) {
+            return Err(Error::NotActive { x, y, active: self });
+        }
         match self {
-            Active::Row(row) => {
-                if y != row {
-                    err
-                } else {
-                    Ok(Active::Column(x))
-                }
-            }
-            Active::Column(column) => {
-                if x != column {
-                    err
-                } else {
-                    Ok(Active::Row(y))
-                }
+            Active::Row(_) => Ok(Active::Column(x)),
+            Active::Column(_) => Ok(Active::Row(y)),
+            Active::Diagonal(_) | Active::AntiDiagonal(_) | Active::Any => {
+                Err(Error::NoPivot { active: self })
             }
         }
     }
 }
 
 /// The Matrix keeps track of the grid of cells and the selections which have been made.
-pub struct Matrix<'a, const WIDTH: usize, const HEIGHT: usize> {
-    values: Grid<InternedString<'a>, WIDTH, HEIGHT>,
+///
+/// Cells hold tokens of type `T`, typically [`InternedString`][crate::InternedString]s or
+/// [`Code`][crate::Code]s. Which cells may be selected is decided by the [`Rules`] `R`.
+#[derive(Debug, Clone)]
+pub struct Matrix<T, const WIDTH: usize, const HEIGHT: usize, R = Standard> {
+    values: Grid<T, WIDTH, HEIGHT>,
     chosen: Grid<bool, WIDTH, HEIGHT>,
+    // shared with the iterators of `legal_selections`, which outlive any borrow of the matrix
+    blocked: Arc<Grid<bool, WIDTH, HEIGHT>>,
     selections: Vec<(usize, usize)>,
+    // the active set before each selection, so that deselection needn't invert the rules
+    previous: Vec<Active>,
     active: Active,
+    rules: R,
 }
 
-impl<'a, const WIDTH: usize, const HEIGHT: usize> Matrix<'a, WIDTH, HEIGHT> {
+/// The selection state of a [`Matrix`] at a point in time.
+///
+/// Snapshots do not include the cell values, so they are cheap to take, and can be
+/// [restored][Matrix::restore] onto any matrix of the same dimensions. The active set isn't
+/// recorded: it follows from the selections under the rules of the matrix restored onto, so the
+/// default snapshot, with nothing selected, restores the initial state under any rules.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    selections: Vec<(usize, usize)>,
+}
+
+impl<T, const WIDTH: usize, const HEIGHT: usize> Matrix<T, WIDTH, HEIGHT>
+where
+    T: Copy,
+{
+    /// Create a new matrix from an iterable of stringy things, in row-major order, looking up
+    /// each item's token through `interner`.
+    ///
+    /// Exactly `WIDTH * HEIGHT` items must be provided.
+    pub fn new<L, Items, Item>(interner: L, items: Items) -> Result<Self, Error>
+    where
+        L: Lookup<Token = T>,
+        Items: IntoIterator<Item = Item>,
+        Item: AsRef<str>,
+    {
+        let items = make_interned(interner, items).map_err(Error::Interning)?;
+        Self::from_values(items)
+    }
+
+    /// Create a new matrix from an iterable of tokens, in row-major order.
+    ///
+    /// Exactly `WIDTH * HEIGHT` items must be provided.
+    ///
+    /// ```rust
+    /// # use breach_protocol::{Code, Matrix};
+    /// let codes = Code::parse_list("1C 55 55 1C").unwrap();
+    /// let matrix = Matrix::<_, 2, 2>::from_values(codes).unwrap();
+    /// assert_eq!(matrix.get(1, 0), Some(Code(0x55)));
+    /// ```
+    pub fn from_values(items: impl IntoIterator<Item = T>) -> Result<Self, Error> {
+        let items: Vec<_> = items.into_iter().collect();
+        let actual = items.len();
+        let values = Grid::from_vec(items).ok_or(Error::WrongSize {
+            expected: WIDTH * HEIGHT,
+            actual,
+        })?;
+        Ok(Self::from_grid(values))
+    }
+
+    /// Create a new matrix from a grid of values.
+    pub(crate) fn from_grid(values: Grid<T, WIDTH, HEIGHT>) -> Self {
+        Self {
+            values,
+            chosen: Grid::new(),
+            blocked: Arc::new(Grid::new()),
+            selections: Vec::new(),
+            previous: Vec::new(),
+            active: Active::default(),
+            rules: Standard::default(),
+        }
+    }
+}
+
+impl<T, const WIDTH: usize, const HEIGHT: usize, R> Matrix<T, WIDTH, HEIGHT, R>
+where
+    T: Copy,
+    R: Rules,
+{
+    /// Play this matrix under different rules.
+    ///
+    /// Any selections are discarded, and the initial active set of the new rules applies.
+    pub fn with_rules<Rules2: Rules>(self, rules: Rules2) -> Matrix<T, WIDTH, HEIGHT, Rules2> {
+        let mut matrix = Matrix {
+            values: self.values,
+            chosen: self.chosen,
+            blocked: self.blocked,
+            selections: self.selections,
+            previous: self.previous,
+            active: self.active,
+            rules,
+        };
+        matrix.reset();
+        matrix
+    }
+
+    /// The rules which govern selection from this matrix
+    pub fn rules(&self) -> &R {
+        &self.rules
+    }
+
+    /// Deselect everything, returning to the initial active set of the rules.
+    ///
+    /// Blocked cells stay blocked.
+    pub fn reset(&mut self) {
+        self.chosen = Grid::new();
+        self.selections.clear();
+        self.previous.clear();
+        self.active = self.rules.initial();
+    }
+
     fn check_bounds(x: usize, y: usize) -> Result<(), Error> {
         if x < WIDTH && y < HEIGHT {
             Ok(())
@@ -63,35 +195,145 @@ impl<'a, const WIDTH: usize, const HEIGHT: usize> Matrix<'a, WIDTH, HEIGHT> {
     /// Select the point at the given coordinates if it is legal to do so.
     ///
     /// Return the value at that point.
-    pub fn select(&mut self, x: usize, y: usize) -> Result<Interned<'_, String>, Error> {
+    pub fn select(&mut self, x: usize, y: usize) -> Result<T, Error> {
         Self::check_bounds(x, y)?;
         if self.chosen[(x, y)] {
             return Err(Error::AlreadySelected { x, y });
         }
-        // the following line modifies self, so we can't fail past that point
-        self.active = self.active.toggle(x, y)?;
+        if self.blocked[(x, y)] {
+            return Err(Error::Blocked { x, y });
+        }
+        if !self.active.contains(x, y) {
+            return Err(Error::NotActive {
+                x,
+                y,
+                active: self.active,
+            });
+        }
+        self.previous.push(self.active);
+        self.active = self.rules.next(self.active, x, y);
         self.chosen[(x, y)] = true;
-
         self.selections.push((x, y));
 
         Ok(self.values[(x, y)])
     }
 
-    /// Deselect the most recent point selected.
+    /// Deselect the most recent point selected, restoring the previous active set.
     ///
-    /// If the selection queue is empty, silently do nothing.
-    pub fn deselect(&mut self) {
-        if let Some((x, y)) = self.selections.pop() {
-            debug_assert!(self.chosen[(x, y)], "point must already have been selected");
-            self.chosen[(x, y)] = false;
-            self.active
-                .toggle(x, y)
-                .expect("toggle must be valid at this point");
+    /// Return the deselected point, or `None` if the selection queue was empty.
+    pub fn deselect(&mut self) -> Option<(usize, usize)> {
+        let (x, y) = self.selections.pop()?;
+        debug_assert!(self.chosen[(x, y)], "point must already have been selected");
+        self.chosen[(x, y)] = false;
+        self.active = self
+            .previous
+            .pop()
+            .expect("every selection records its previous active set");
+        Some((x, y))
+    }
+
+    /// Capture the current selection state.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            selections: self.selections.clone(),
+        }
+    }
+
+    /// Restore a selection state previously captured by [`snapshot`][Self::snapshot].
+    ///
+    /// The selections are replayed under the rules of this matrix, which decide the active set.
+    /// They are checked before anything is modified; if any of them would be illegal, this
+    /// matrix is unchanged. This happens when the snapshot was taken from a matrix with different
+    /// rules, or cells have since been blocked.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), Error> {
+        let mut active = self.rules.initial();
+        for (idx, &(x, y)) in snapshot.selections.iter().enumerate() {
+            Self::check_bounds(x, y)?;
+            if !active.contains(x, y) {
+                return Err(Error::NotActive { x, y, active });
+            }
+            if snapshot.selections[..idx].contains(&(x, y)) {
+                return Err(Error::AlreadySelected { x, y });
+            }
+            if self.blocked[(x, y)] {
+                return Err(Error::Blocked { x, y });
+            }
+            active = self.rules.next(active, x, y);
+        }
+
+        self.reset();
+        for &(x, y) in &snapshot.selections {
+            self.chosen[(x, y)] = true;
+            // recover the active set preceding each selection by replaying the rules
+            self.previous.push(self.active);
+            self.active = self.rules.next(self.active, x, y);
+        }
+        self.selections.clone_from(&snapshot.selections);
+        Ok(())
+    }
+
+    /// Prevent the cell at the given coordinates from being selected.
+    ///
+    /// This models cells which are damaged, already used up, or otherwise out of play. Blocked
+    /// cells keep their values, but are never legal selections. A cell which is currently
+    /// selected can't be blocked.
+    ///
+    /// ```rust
+    /// # use breach_protocol::{Code, Matrix, MatrixError};
+    /// let codes = Code::parse_list("1C 55 55 1C").unwrap();
+    /// let mut matrix = Matrix::<_, 2, 2>::from_values(codes).unwrap();
+    /// matrix.block(0, 0).unwrap();
+    /// assert_eq!(matrix.legal_selections().collect::<Vec<_>>(), [(1, 0)]);
+    /// assert!(matches!(matrix.select(0, 0), Err(MatrixError::Blocked { x: 0, y: 0 })));
+    /// ```
+    pub fn block(&mut self, x: usize, y: usize) -> Result<(), Error> {
+        Self::check_bounds(x, y)?;
+        if self.chosen[(x, y)] {
+            return Err(Error::AlreadySelected { x, y });
         }
+        Arc::make_mut(&mut self.blocked)[(x, y)] = true;
+        Ok(())
+    }
+
+    /// Allow a blocked cell to be selected again.
+    pub fn unblock(&mut self, x: usize, y: usize) -> Result<(), Error> {
+        Self::check_bounds(x, y)?;
+        Arc::make_mut(&mut self.blocked)[(x, y)] = false;
+        Ok(())
+    }
+
+    /// `true` when the point at the given coordinates has been [blocked][Self::block]
+    pub fn is_blocked(&self, x: usize, y: usize) -> bool {
+        self.blocked.get(x, y).copied().unwrap_or_default()
+    }
+
+    /// The currently active row or column
+    pub fn active(&self) -> Active {
+        self.active
+    }
+
+    /// The values of this matrix
+    pub fn values(&self) -> &Grid<T, WIDTH, HEIGHT> {
+        &self.values
+    }
+
+    /// The value at the given point, if it is in bounds
+    pub fn get(&self, x: usize, y: usize) -> Option<T> {
+        self.values.get(x, y).copied()
+    }
+
+    /// `true` when the point at the given coordinates has been selected
+    pub fn is_chosen(&self, x: usize, y: usize) -> bool {
+        self.chosen.get(x, y).copied().unwrap_or_default()
+    }
+
+    /// The selected points, in order of selection
+    pub fn selections(&self) -> &[(usize, usize)] {
+        &self.selections
     }
 
     /// Iterate over the selected values
-    pub fn selected_values(&self) -> impl Iterator<Item = Interned<'_, String>> {
+    pub fn selected_values(&self) -> impl '_ + Iterator<Item = T> {
         self.selections
             .iter()
             .copied()
@@ -110,21 +352,19 @@ impl<'a, const WIDTH: usize, const HEIGHT: usize> Matrix<'a, WIDTH, HEIGHT> {
     ///
     /// This is intended to make recursive push/pop algorithms possible, but this function should be used
     /// with caution.
+    ///
+    /// Points are produced in row-major order. Points which have already been chosen are included;
+    /// blocked points are not.
     pub fn legal_selections(&self) -> impl Iterator<Item = (usize, usize)> {
-        let active = self.active;
-        (0..)
-            .map(move |t| match active {
-                Active::Row(y) => (t, y),
-                Active::Column(x) => (x, t),
-            })
-            .filter(|(x, y)| Self::check_bounds(*x, *y).is_ok())
-            .fuse()
+        let blocked = Arc::clone(&self.blocked);
+        Grid::<T, WIDTH, HEIGHT>::line_points(self.active).filter(move |&point| !blocked[point])
     }
 }
 
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
-    #[error("the point 1({x}, {y})` is out of bounds. max: `({width}, {height})`")]
+    #[error("the point `({x}, {y})` is out of bounds of a {width}x{height} matrix")]
     OutOfBounds {
         x: usize,
         y: usize,
@@ -135,4 +375,231 @@ pub enum Error {
     NotActive { x: usize, y: usize, active: Active },
     #[error("the point `({x}, {y})` has already been selected")]
     AlreadySelected { x: usize, y: usize },
+    #[error("the point `({x}, {y})` is blocked")]
+    Blocked { x: usize, y: usize },
+    #[error("only rows and columns pivot, not {active:?}")]
+    NoPivot { active: Active },
+    #[error("expected {expected} items to construct matrix but got {actual}")]
+    WrongSize { expected: usize, actual: usize },
+    #[error("interning matrix values")]
+    Interning(#[source] crate::sequence::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rules::Free,
+        test_support::{make_interner, make_matrix},
+    };
+
+    #[test]
+    fn deselect_restores_active() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        matrix.select(2, 0).unwrap();
+        matrix.select(2, 1).unwrap();
+        assert_eq!(matrix.active(), Active::Row(1));
+
+        assert_eq!(matrix.deselect(), Some((2, 1)));
+        assert_eq!(matrix.active(), Active::Column(2));
+        assert!(!matrix.is_chosen(2, 1));
+        assert_eq!(matrix.deselect(), Some((2, 0)));
+        assert_eq!(matrix.active(), Active::Row(0));
+        assert_eq!(matrix.deselect(), None);
+    }
+
+    #[test]
+    fn legal_selections_end_at_edge() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        assert_eq!(
+            matrix.legal_selections().collect::<Vec<_>>(),
+            [(0, 0), (1, 0), (2, 0)]
+        );
+        matrix.select(1, 0).unwrap();
+        assert_eq!(
+            matrix.legal_selections().collect::<Vec<_>>(),
+            [(1, 0), (1, 1), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn snapshot_restore() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        matrix.select(0, 0).unwrap();
+        matrix.select(0, 2).unwrap();
+        let snapshot = matrix.snapshot();
+
+        matrix.deselect();
+        matrix.select(0, 1).unwrap();
+        matrix.restore(&snapshot).unwrap();
+
+        assert_eq!(matrix.selections(), [(0, 0), (0, 2)]);
+        assert_eq!(matrix.active(), Active::Row(2));
+        assert!(matrix.is_chosen(0, 2));
+        assert!(!matrix.is_chosen(0, 1));
+    }
+
+    #[test]
+    fn blocked_cells() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        matrix.block(1, 0).unwrap();
+        assert!(matrix.is_blocked(1, 0));
+        assert_eq!(
+            matrix.legal_selections().collect::<Vec<_>>(),
+            [(0, 0), (2, 0)]
+        );
+        // iterators see the cells blocked when they were created
+        let legal = matrix.legal_selections();
+        matrix.block(2, 0).unwrap();
+        assert_eq!(legal.collect::<Vec<_>>(), [(0, 0), (2, 0)]);
+        matrix.unblock(2, 0).unwrap();
+        assert!(matches!(
+            matrix.select(1, 0),
+            Err(Error::Blocked { x: 1, y: 0 })
+        ));
+
+        // selected cells can't be blocked, and blocked cells can't be restored as selected
+        matrix.select(0, 0).unwrap();
+        assert!(matches!(
+            matrix.block(0, 0),
+            Err(Error::AlreadySelected { .. })
+        ));
+        let snapshot = matrix.snapshot();
+        matrix.reset();
+        assert!(matrix.is_blocked(1, 0));
+        matrix.block(0, 0).unwrap();
+        assert!(matches!(
+            matrix.restore(&snapshot),
+            Err(Error::Blocked { x: 0, y: 0 })
+        ));
+
+        matrix.unblock(0, 0).unwrap();
+        matrix.restore(&snapshot).unwrap();
+        assert_eq!(matrix.selections(), [(0, 0)]);
+    }
+
+    #[test]
+    fn restore_under_other_rules() {
+        let interner = make_interner();
+        let mut free = make_matrix(&interner).with_rules(Free);
+        free.select(2, 2).unwrap();
+        free.select(0, 1).unwrap();
+        let snapshot = free.snapshot();
+
+        let mut matrix = make_matrix(&interner);
+        matrix.select(1, 0).unwrap();
+        assert!(matches!(
+            matrix.restore(&snapshot),
+            Err(Error::NotActive {
+                x: 2,
+                y: 2,
+                active: Active::Row(0)
+            })
+        ));
+        assert_eq!(matrix.selections(), [(1, 0)]);
+    }
+
+    #[test]
+    fn restore_standard_active() {
+        let interner = make_interner();
+        let mut free = make_matrix(&interner).with_rules(Free);
+        free.select(0, 0).unwrap();
+        let snapshot = free.snapshot();
+
+        let mut matrix = make_matrix(&interner);
+        matrix.restore(&snapshot).unwrap();
+        assert_eq!(matrix.active(), Active::Column(0));
+        assert!(matches!(
+            matrix.select(2, 2),
+            Err(Error::NotActive {
+                active: Active::Column(0),
+                ..
+            })
+        ));
+        matrix.select(0, 2).unwrap();
+
+        matrix.restore(&Snapshot::default()).unwrap();
+        assert_eq!(matrix.active(), Active::Row(0));
+    }
+
+    #[test]
+    fn restore_free_active() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner).with_rules(Free);
+        matrix.select(2, 1).unwrap();
+        matrix.restore(&Snapshot::default()).unwrap();
+        assert_eq!(matrix.selected_len(), 0);
+        assert_eq!(matrix.active(), Active::Any);
+        matrix.select(1, 1).unwrap();
+
+        let snapshot = matrix.snapshot();
+        matrix.select(0, 2).unwrap();
+        matrix.restore(&snapshot).unwrap();
+        assert_eq!(matrix.selections(), [(1, 1)]);
+        assert_eq!(matrix.active(), Active::Any);
+        assert_eq!(matrix.deselect(), Some((1, 1)));
+        assert_eq!(matrix.active(), Active::Any);
+    }
+
+    #[test]
+    fn toggle() {
+        assert_eq!(Active::Row(1).toggle(2, 1).unwrap(), Active::Column(2));
+        assert_eq!(Active::Column(2).toggle(2, 0).unwrap(), Active::Row(0));
+        assert!(matches!(
+            Active::Row(1).toggle(2, 0),
+            Err(Error::NotActive { .. })
+        ));
+        assert!(matches!(
+            Active::Diagonal(0).toggle(1, 1),
+            Err(Error::NoPivot {
+                active: Active::Diagonal(0)
+            })
+        ));
+        assert!(matches!(
+            Active::Any.toggle(1, 1),
+            Err(Error::NoPivot { .. })
+        ));
+    }
+
+    #[test]
+    fn custom_rules() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner).with_rules(Free);
+        matrix.select(2, 2).unwrap();
+        matrix.select(0, 1).unwrap();
+        assert_eq!(matrix.legal_selections().count(), 9);
+        assert_eq!(matrix.deselect(), Some((0, 1)));
+        assert_eq!(matrix.active(), Active::Any);
+
+        let start = Standard {
+            start: Active::Column(1),
+        };
+        let mut matrix = make_matrix(&interner).with_rules(start);
+        assert!(matches!(matrix.select(0, 0), Err(Error::NotActive { .. })));
+        matrix.select(1, 2).unwrap();
+        assert_eq!(matrix.active(), Active::Row(2));
+        let snapshot = matrix.snapshot();
+        matrix.reset();
+        assert_eq!(matrix.active(), Active::Column(1));
+        matrix.restore(&snapshot).unwrap();
+        assert_eq!(matrix.deselect(), Some((1, 2)));
+        assert_eq!(matrix.active(), Active::Column(1));
+    }
+
+    #[test]
+    fn wrong_size() {
+        let interner = make_interner();
+        let result = Matrix::<_, 3, 3>::new(&interner, ["1A"]);
+        assert!(matches!(
+            result,
+            Err(Error::WrongSize {
+                expected: 9,
+                actual: 1
+            })
+        ));
+    }
 }
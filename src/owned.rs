@@ -0,0 +1,348 @@
+use crate::{
+    grid::Grid,
+    interner::{InternedString, Interner, Symbol},
+    matrix::{self, Matrix},
+    sequence::{self, make_interned, Pattern, Sequence},
+    validate::{self, validate},
+    BreachProtocol, Solution,
+};
+
+/// Call a function generic over the matrix dimensions with the appropriate const parameters.
+///
+/// Evaluates to `Err(Error::UnsupportedSize)` for dimensions which aren't supported.
+macro_rules! with_dimensions {
+    ($width:expr, $height:expr, $f:ident($($arg:expr),*)) => {
+        match $width {
+            3 => with_dimensions!(@height 3, $height, $f($($arg),*)),
+            4 => with_dimensions!(@height 4, $height, $f($($arg),*)),
+            5 => with_dimensions!(@height 5, $height, $f($($arg),*)),
+            6 => with_dimensions!(@height 6, $height, $f($($arg),*)),
+            7 => with_dimensions!(@height 7, $height, $f($($arg),*)),
+            8 => with_dimensions!(@height 8, $height, $f($($arg),*)),
+            width => Err(Error::UnsupportedSize { width, height: $height }),
+        }
+    };
+    (@height $width:literal, $height:expr, $f:ident($($arg:expr),*)) => {
+        match $height {
+            3 => $f::<$width, 3>($($arg),*),
+            4 => $f::<$width, 4>($($arg),*),
+            5 => $f::<$width, 5>($($arg),*),
+            6 => $f::<$width, 6>($($arg),*),
+            7 => $f::<$width, 7>($($arg),*),
+            8 => $f::<$width, 8>($($arg),*),
+            height => Err(Error::UnsupportedSize { width: $width, height }),
+        }
+    };
+}
+
+/// Describe an error and all its sources.
+#[cfg(any(feature = "python", feature = "serve"))]
+pub(crate) fn describe(err: &dyn std::error::Error) -> String {
+    let mut description = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        description.push_str(": ");
+        description.push_str(&err.to_string());
+        source = err.source();
+    }
+    description
+}
+
+/// A puzzle whose dimensions are only known at runtime.
+///
+/// This is the storage behind [`Puzzle`][crate::Puzzle], which fixes the dimensions at compile
+/// time, and is used directly by the language bindings. Matrix dimensions are const generics,
+/// so solving and validating dispatch to a monomorphized implementation; only matrices from 3x3
+/// to 8x8 are supported there.
+#[derive(Debug, Clone)]
+pub(crate) struct OwnedPuzzle {
+    interner: Interner<String>,
+    protocol: BreachProtocol,
+    width: usize,
+    height: usize,
+    values: Vec<Symbol>,
+    sequences: Vec<Vec<Pattern<Symbol>>>,
+    blocked: Vec<(usize, usize)>,
+}
+
+/// A [`Solution`] which doesn't borrow from an interner.
+///
+/// Its buffer can be recovered from the path with [`OwnedPuzzle::value`].
+#[derive(Debug, Clone)]
+pub(crate) struct OwnedSolution {
+    pub path: Vec<(usize, usize)>,
+    pub matches: Vec<usize>,
+}
+
+impl From<Solution<InternedString<'_>>> for OwnedSolution {
+    fn from(solution: Solution<InternedString<'_>>) -> Self {
+        Self {
+            path: solution.path().to_vec(),
+            matches: solution.matches().to_vec(),
+        }
+    }
+}
+
+fn solve<const WIDTH: usize, const HEIGHT: usize>(
+    puzzle: &OwnedPuzzle,
+) -> Result<Vec<OwnedSolution>, Error> {
+    Ok(puzzle
+        .protocol
+        .solve(&mut puzzle.matrix::<WIDTH, HEIGHT>(), &puzzle.sequences())
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+fn check_path<const WIDTH: usize, const HEIGHT: usize>(
+    puzzle: &OwnedPuzzle,
+    path: &[(usize, usize)],
+) -> Result<(OwnedSolution, Option<validate::Error>), Error> {
+    let matrix = puzzle.matrix::<WIDTH, HEIGHT>();
+    let (solution, error) =
+        validate(&matrix, &puzzle.sequences(), puzzle.buffer_size(), path).into_parts();
+    Ok((solution.into(), error))
+}
+
+impl OwnedPuzzle {
+    /// Create a puzzle from `width * height` tokens in row-major order.
+    ///
+    /// Fails if the puzzle can't be solved because its dimensions aren't supported.
+    pub fn new(
+        values: Vec<String>,
+        width: usize,
+        height: usize,
+        buffer_size: usize,
+    ) -> Result<Self, Error> {
+        if !(matches!(width, 3..=8) && matches!(height, 3..=8)) {
+            return Err(Error::UnsupportedSize { width, height });
+        }
+        Ok(Self::with_size(values, width, height, buffer_size)?)
+    }
+
+    /// Create a puzzle from `width * height` tokens in row-major order, of any dimensions.
+    pub fn with_size<Items, Item>(
+        items: Items,
+        width: usize,
+        height: usize,
+        buffer_size: usize,
+    ) -> Result<Self, matrix::Error>
+    where
+        Items: IntoIterator<Item = Item>,
+        Item: AsRef<str>,
+    {
+        let items: Vec<String> = items
+            .into_iter()
+            .map(|item| item.as_ref().to_owned())
+            .collect();
+        if items.len() != width * height {
+            return Err(matrix::Error::WrongSize {
+                expected: width * height,
+                actual: items.len(),
+            });
+        }
+        let mut interner = Interner::new();
+        interner.extend(items.clone());
+
+        let values = make_interned(&interner, &items)
+            .map_err(matrix::Error::Interning)?
+            .into_iter()
+            .map(|value| value.symbol())
+            .collect();
+
+        Ok(Self {
+            interner,
+            protocol: BreachProtocol::new(buffer_size),
+            width,
+            height,
+            values,
+            sequences: Vec::new(),
+            blocked: Vec::new(),
+        })
+    }
+
+    /// Add a sequence of patterns in their text form. Every token must appear in the matrix.
+    pub fn add_sequence<Items, Item>(&mut self, items: Items) -> Result<(), sequence::Error>
+    where
+        Items: IntoIterator<Item = Item>,
+        Item: AsRef<str>,
+    {
+        let patterns = Sequence::parse(&self.interner, items)?
+            .items()
+            .iter()
+            .map(|pattern| pattern.clone().map(|item| item.symbol()))
+            .collect();
+        self.sequences.push(patterns);
+        Ok(())
+    }
+
+    /// Prevent the cell at the given coordinates from being selected. Blocking a cell twice has
+    /// no further effect.
+    pub fn block(&mut self, x: usize, y: usize) -> Result<(), matrix::Error> {
+        if x >= self.width || y >= self.height {
+            return Err(matrix::Error::OutOfBounds {
+                x,
+                y,
+                width: self.width,
+                height: self.height,
+            });
+        }
+        if !self.blocked.contains(&(x, y)) {
+            self.blocked.push((x, y));
+        }
+        Ok(())
+    }
+
+    pub fn protocol(&self) -> &BreachProtocol {
+        &self.protocol
+    }
+
+    pub fn interner(&self) -> &Interner<String> {
+        &self.interner
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.protocol.buffer_size()
+    }
+
+    /// The symbol at the given point, if it is in bounds
+    pub fn get(&self, x: usize, y: usize) -> Option<Symbol> {
+        (x < self.width && y < self.height).then(|| self.values[y * self.width + x])
+    }
+
+    /// The token at the given point.
+    #[cfg(any(feature = "python", feature = "serve"))]
+    pub fn value(&self, x: usize, y: usize) -> &str {
+        let symbol = self.get(x, y).expect("point is in bounds");
+        self.interner()
+            .resolve(symbol)
+            .expect("puzzle symbols are always valid for its interner")
+    }
+
+    pub fn sequence_symbols(&self) -> &[Vec<Pattern<Symbol>>] {
+        &self.sequences
+    }
+
+    fn intern(&self, symbol: Symbol) -> InternedString<'_> {
+        self.interner()
+            .get_symbol(symbol)
+            .expect("puzzle symbols are always valid for its interner")
+    }
+
+    /// A matrix of this puzzle's values, with nothing selected and its blocked cells blocked.
+    ///
+    /// `WIDTH` and `HEIGHT` must be this puzzle's dimensions.
+    pub fn matrix<const WIDTH: usize, const HEIGHT: usize>(
+        &self,
+    ) -> Matrix<InternedString<'_>, WIDTH, HEIGHT> {
+        let values = self
+            .values
+            .iter()
+            .map(|&symbol| self.intern(symbol))
+            .collect();
+        let values = Grid::from_vec(values).expect("matrix dimensions match the puzzle");
+        let mut matrix = Matrix::from_grid(values);
+        for &(x, y) in &self.blocked {
+            matrix
+                .block(x, y)
+                .expect("blocked cells were checked when added");
+        }
+        matrix
+    }
+
+    /// This puzzle's sequences, in order of addition.
+    pub fn sequences(&self) -> Vec<Sequence<InternedString<'_>>> {
+        self.sequences
+            .iter()
+            .map(|patterns| {
+                Sequence::from_patterns(
+                    patterns
+                        .iter()
+                        .map(|pattern| pattern.clone().map(|symbol| self.intern(symbol))),
+                )
+            })
+            .collect()
+    }
+
+    /// Find all solutions which complete at least one sequence.
+    pub fn solve(&self) -> Result<Vec<OwnedSolution>, Error> {
+        with_dimensions!(self.width, self.height, solve(self))
+    }
+
+    /// Check a path against this puzzle as [`validate`] does.
+    ///
+    /// Return the solution produced by the legal prefix of the path, and the first illegal step.
+    pub fn check_path(
+        &self,
+        path: &[(usize, usize)],
+    ) -> Result<(OwnedSolution, Option<validate::Error>), Error> {
+        with_dimensions!(self.width, self.height, check_path(self, path))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("unsupported matrix dimensions: {width}x{height}, both must be from 3 to 8")]
+    UnsupportedSize { width: usize, height: usize },
+    #[error(transparent)]
+    Matrix(#[from] matrix::Error),
+    #[error(transparent)]
+    Sequence(#[from] sequence::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(s: &str) -> Vec<String> {
+        s.split_ascii_whitespace().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn rectangular() {
+        let mut puzzle =
+            OwnedPuzzle::new(tokens("1A 2B 3C 4D 2B 3C 4D 1A 3C 4D 1A 2B"), 4, 3, 3).unwrap();
+        puzzle.add_sequence(["4D", "1A"]).unwrap();
+        let solutions = puzzle.solve().unwrap();
+        assert!(solutions
+            .iter()
+            .any(|solution| solution.path.starts_with(&[(3, 0), (3, 1)])));
+        assert_eq!(puzzle.get(3, 2), puzzle.get(0, 1));
+        assert_eq!(puzzle.get(4, 0), None);
+    }
+
+    #[test]
+    fn block() {
+        let mut puzzle = OwnedPuzzle::new(tokens("1A 2B 3C 2B 3C 1A 3C 1A 2B"), 3, 3, 3).unwrap();
+        puzzle.block(1, 0).unwrap();
+        puzzle.block(1, 0).unwrap();
+        assert_eq!(puzzle.blocked, [(1, 0)]);
+        assert!(matches!(
+            puzzle.block(0, 3),
+            Err(matrix::Error::OutOfBounds { x: 0, y: 3, .. })
+        ));
+        assert!(puzzle.matrix::<3, 3>().is_blocked(1, 0));
+    }
+
+    #[test]
+    fn unsupported_size() {
+        assert!(matches!(
+            OwnedPuzzle::new(tokens("1A 2B"), 2, 1, 3),
+            Err(Error::UnsupportedSize {
+                width: 2,
+                height: 1
+            })
+        ));
+        assert!(matches!(
+            OwnedPuzzle::new(vec!["1A".to_owned(); 27], 9, 3, 3),
+            Err(Error::UnsupportedSize {
+                width: 9,
+                height: 3
+            })
+        ));
+        assert!(matches!(
+            OwnedPuzzle::new(tokens("1A 2B"), 3, 3, 3),
+            Err(Error::Matrix(matrix::Error::WrongSize { .. }))
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    interner::{InternedString, Interner, Symbol},
+    lint::{lint, Issue},
+    matrix::{self, Matrix},
+    owned::OwnedPuzzle,
+    sequence::{self, Pattern, Sequence},
+    validate::{validate, Validation},
+    Solution,
+};
+
+/// A complete puzzle: a matrix, a buffer size, and a list of sequences.
+///
+/// Unlike [`Matrix`] and [`Sequence`], which borrow from an interner, a puzzle owns its interner
+/// and stores its values as [`Symbol`]s. It can therefore be stored in other structs, and moved
+/// across threads and async tasks. Borrowed matrices and sequences are produced on demand.
+///
+/// The interner isn't modified after construction, so the puzzle's symbols remain valid for its
+/// whole lifetime.
+///
+/// ```rust
+/// # use breach_protocol::Puzzle;
+/// let mut puzzle = Puzzle::<2, 2>::new(["1C", "55", "55", "1C"], 2).unwrap();
+/// puzzle.add_sequence(["1C", "55"]).unwrap();
+///
+/// let handle = std::thread::spawn(move || puzzle.solve().len());
+/// assert_eq!(handle.join().unwrap(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Puzzle<const WIDTH: usize, const HEIGHT: usize>(OwnedPuzzle);
+
+impl<const WIDTH: usize, const HEIGHT: usize> Puzzle<WIDTH, HEIGHT> {
+    /// Create a puzzle from an iterable of stringy things, in row-major order.
+    ///
+    /// Exactly `WIDTH * HEIGHT` items must be provided.
+    pub fn new<Items, Item>(items: Items, buffer_size: usize) -> Result<Self, matrix::Error>
+    where
+        Items: IntoIterator<Item = Item>,
+        Item: AsRef<str>,
+    {
+        OwnedPuzzle::with_size(items, WIDTH, HEIGHT, buffer_size).map(Self)
+    }
+
+    /// Add a sequence of [`Pattern`]s in their text form, as [`Sequence::parse`] takes them.
+    /// Every token must appear in the matrix.
+    pub fn add_sequence<Items, Item>(&mut self, items: Items) -> Result<(), sequence::Error>
+    where
+        Items: IntoIterator<Item = Item>,
+        Item: AsRef<str>,
+    {
+        self.0.add_sequence(items)
+    }
+
+    /// Prevent the cell at the given coordinates from being selected, as [`Matrix::block`] does.
+    pub fn block(&mut self, x: usize, y: usize) -> Result<(), matrix::Error> {
+        self.0.block(x, y)
+    }
+
+    /// The interner which resolves this puzzle's symbols
+    pub fn interner(&self) -> &Interner<String> {
+        self.0.interner()
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.0.buffer_size()
+    }
+
+    /// The symbol at the given point, if it is in bounds
+    pub fn get(&self, x: usize, y: usize) -> Option<Symbol> {
+        self.0.get(x, y)
+    }
+
+    /// The sequences of this puzzle, as patterns of symbols
+    pub fn sequence_symbols(&self) -> &[Vec<Pattern<Symbol>>] {
+        self.0.sequence_symbols()
+    }
+
+    /// A matrix of this puzzle's values, with nothing selected and its blocked cells blocked.
+    pub fn matrix(&self) -> Matrix<InternedString<'_>, WIDTH, HEIGHT> {
+        self.0.matrix()
+    }
+
+    /// This puzzle's sequences, in order of addition.
+    pub fn sequences(&self) -> Vec<Sequence<InternedString<'_>>> {
+        self.0.sequences()
+    }
+
+    /// Check this puzzle for problems as [`lint`] does.
+    pub fn lint(&self) -> Vec<Issue> {
+        lint(&self.matrix(), &self.sequences(), self.buffer_size())
+    }
+
+    /// Find all solutions which complete at least one sequence.
+    pub fn solve(&self) -> Vec<Solution<InternedString<'_>>> {
+        self.0
+            .protocol()
+            .solve(&mut self.matrix(), &self.sequences())
+    }
+
+    /// Check a path against this puzzle as [`validate`] does.
+    pub fn validate(&self, path: &[(usize, usize)]) -> Validation<InternedString<'_>> {
+        validate(&self.matrix(), &self.sequences(), self.buffer_size(), path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+    #[test]
+    fn puzzle_is_send_sync() {
+        assert_send_sync::<Puzzle<5, 5>>();
+    }
+
+    #[test]
+    fn symbols_resolve() {
+        let mut puzzle =
+            Puzzle::<3, 3>::new("1A 2B 3C 2B 3C 1A 3C 1A 2B".split_ascii_whitespace(), 3).unwrap();
+        puzzle.add_sequence(["2B", "1A"]).unwrap();
+
+        let symbol = puzzle.get(1, 0).unwrap();
+        assert_eq!(puzzle.interner().resolve(symbol).unwrap(), "2B");
+        assert_eq!(puzzle.matrix().get(1, 0).unwrap().symbol(), symbol);
+        assert_eq!(puzzle.sequence_symbols()[0][0], Pattern::Exact(symbol));
+
+        let solutions = puzzle.solve();
+        assert!(solutions
+            .iter()
+            .any(|solution| solution.path() == [(1, 0), (1, 2), (0, 2)]));
+        assert!(puzzle.validate(&[(1, 0), (1, 2)]).is_valid());
+    }
+
+    #[test]
+    fn blocked_cells() {
+        let mut puzzle =
+            Puzzle::<3, 3>::new("1A 2B 3C 2B 3C 1A 3C 1A 2B".split_ascii_whitespace(), 3).unwrap();
+        puzzle.add_sequence(["2B", "1A"]).unwrap();
+        assert!(!puzzle.solve().is_empty());
+
+        puzzle.block(1, 0).unwrap();
+        puzzle.block(1, 2).unwrap();
+        assert!(matches!(
+            puzzle.block(3, 0),
+            Err(matrix::Error::OutOfBounds { .. })
+        ));
+
+        let matrix = puzzle.matrix();
+        let solutions = puzzle.solve();
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            assert!(solution
+                .path()
+                .iter()
+                .all(|&(x, y)| !matrix.is_blocked(x, y)));
+        }
+        assert!(!puzzle.validate(&[(1, 0), (1, 2)]).is_valid());
+    }
+
+    #[test]
+    fn patterns() {
+        let mut puzzle =
+            Puzzle::<3, 3>::new("1A 2B 3C 2B 3C 1A 3C 1A 2B".split_ascii_whitespace(), 3).unwrap();
+        puzzle.add_sequence(["2B", "*", "1A|3C"]).unwrap();
+        assert_eq!(puzzle.sequence_symbols()[0][1], Pattern::Any);
+        assert_eq!(puzzle.sequences()[0].to_string(), "2B * 1A|3C");
+        let validation = puzzle.validate(&[(1, 0), (1, 2), (0, 2)]);
+        assert_eq!(validation.solution().matches(), [0]);
+        let validation = puzzle.validate(&[(1, 0), (1, 1), (0, 1)]);
+        assert!(validation.solution().matches().is_empty());
+    }
+
+    #[test]
+    fn unknown_sequence_item() {
+        let mut puzzle = Puzzle::<2, 2>::new(["1A", "2B", "2B", "1A"], 2).unwrap();
+        assert!(matches!(
+            puzzle.add_sequence(["3C"]),
+            Err(sequence::Error::NotFound(item)) if item == "3C"
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+// Python bindings, built with the `python` feature.
+//
+// Everything crossing into Python is converted to plain lists, tuples and dicts, so that no
+// interned values, and hence no interner lifetimes, are visible from Python.
+
+// pyo3's generated wrappers trip this lint on every `PyResult` return
+#![allow(clippy::useless_conversion)]
+
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyDict, PyList},
+};
+
+use crate::{
+    interner::Interner,
+    owned::{self, describe, OwnedPuzzle, OwnedSolution},
+    sequence::{make_interned, Pattern, Sequence},
+};
+
+fn value_error(err: impl std::error::Error) -> PyErr {
+    PyValueError::new_err(describe(&err))
+}
+
+impl From<owned::Error> for PyErr {
+    fn from(err: owned::Error) -> Self {
+        value_error(err)
+    }
+}
+
+/// A puzzle: a matrix of tokens, a buffer size, and a list of sequences.
+#[pyclass(name = "Puzzle", module = "breach_protocol")]
+struct Puzzle(OwnedPuzzle);
+
+impl Puzzle {
+    fn solution_dict<'py>(
+        &self,
+        py: Python<'py>,
+        solution: &OwnedSolution,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new_bound(py);
+        let buffer: Vec<_> = solution
+            .path
+            .iter()
+            .map(|&(x, y)| self.0.value(x, y))
+            .collect();
+        dict.set_item("path", PyList::new_bound(py, &solution.path))?;
+        dict.set_item("buffer", buffer)?;
+        dict.set_item("matches", &solution.matches)?;
+        Ok(dict)
+    }
+}
+
+#[pymethods]
+impl Puzzle {
+    /// Create a puzzle from a list of rows of tokens.
+    #[new]
+    #[pyo3(signature = (matrix, buffer_size, sequences = Vec::new()))]
+    fn new(
+        matrix: Vec<Vec<String>>,
+        buffer_size: usize,
+        sequences: Vec<Vec<String>>,
+    ) -> PyResult<Self> {
+        let height = matrix.len();
+        let width = matrix.first().map_or(0, Vec::len);
+        if matrix.iter().any(|row| row.len() != width) {
+            return Err(PyValueError::new_err(
+                "matrix rows must all have the same length",
+            ));
+        }
+        let values = matrix.into_iter().flatten().collect();
+        let mut puzzle = OwnedPuzzle::new(values, width, height, buffer_size)?;
+        for sequence in sequences {
+            puzzle.add_sequence(sequence).map_err(value_error)?;
+        }
+        Ok(Self(puzzle))
+    }
+
+    /// Add a sequence of patterns: tokens, `*` to match any token, or tokens joined by `|` to
+    /// match any one of them. Every token must appear in the matrix.
+    fn add_sequence(&mut self, sequence: Vec<String>) -> PyResult<()> {
+        self.0.add_sequence(sequence).map_err(value_error)
+    }
+
+    /// Prevent the cell at `(x, y)` from being selected.
+    fn block(&mut self, x: usize, y: usize) -> PyResult<()> {
+        self.0.block(x, y).map_err(value_error)
+    }
+
+    /// Find all solutions which complete at least one sequence.
+    ///
+    /// Each solution is a dict with keys `path` (a list of `(x, y)` tuples),
+    /// `buffer` (a list of tokens) and `matches` (a list of sequence indices).
+    fn solve<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let solutions = py.allow_threads(|| self.0.solve())?;
+        solutions
+            .iter()
+            .map(|solution| self.solution_dict(py, solution))
+            .collect()
+    }
+
+    /// Check a path of `(x, y)` tuples against this puzzle.
+    ///
+    /// Return a solution dict for the legal prefix of the path, with the additional keys
+    /// `valid`, `error` (a message, or `None`) and `failed_step` (an index, or `None`).
+    fn validate<'py>(
+        &self,
+        py: Python<'py>,
+        path: Vec<(usize, usize)>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let (solution, error) = self.0.check_path(&path)?;
+        let dict = self.solution_dict(py, &solution)?;
+        dict.set_item("valid", error.is_none())?;
+        dict.set_item(
+            "error",
+            error
+                .as_ref()
+                .map(|err| describe(err as &dyn std::error::Error)),
+        )?;
+        dict.set_item(
+            "failed_step",
+            error.is_some().then_some(solution.path.len()),
+        )?;
+        Ok(dict)
+    }
+}
+
+/// `True` when `sequence` appears, in order and contiguously, within `buffer`. Sequence items
+/// may be patterns in their text form.
+#[pyfunction]
+fn sequence_matched(sequence: Vec<String>, buffer: Vec<String>) -> PyResult<bool> {
+    let Ok(patterns) = sequence
+        .iter()
+        .map(|item| item.parse::<Pattern<String>>())
+        .collect::<Result<Vec<_>, _>>();
+    let mut interner = Interner::new();
+    let tokens = patterns.iter().flat_map(|pattern| pattern.tokens());
+    interner.extend(buffer.iter().chain(tokens).cloned().collect());
+    let sequence = Sequence::from_patterns(patterns.into_iter().map(|pattern| {
+        pattern.map(|token| interner.get(&token).expect("pattern tokens were interned"))
+    }));
+    let buffer = make_interned(&interner, &buffer).map_err(value_error)?;
+    Ok(sequence.is_matched(&buffer))
+}
+
+#[pymodule]
+fn breach_protocol(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Puzzle>()?;
+    module.add_function(wrap_pyfunction!(sequence_matched, module)?)?;
+    Ok(())
+}
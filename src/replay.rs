@@ -0,0 +1,348 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    matrix::Matrix,
+    rules::{Rules, Standard},
+    sequence::Sequence,
+    validate::validate,
+    Solution,
+};
+
+const HEADER: &str = "breach/2";
+
+/// A recorded list of moves, which can be shared and replayed against a puzzle.
+///
+/// ## Format
+///
+/// Replays serialize to a single line of whitespace-separated fields: a version header,
+/// the matrix dimensions, the [name][Rules::name] of the rules played under, one `x,y` field per
+/// move, and a checksum of everything preceding it.
+///
+/// ```text
+/// breach/2 5x5 standard 1,0 1,3 4,3 #7c276664
+/// ```
+///
+/// The checksum only guards against accidental corruption. Deliberately altered moves are
+/// caught when the replay is [verified][Replay::verify].
+///
+/// ```rust
+/// # use breach_protocol::{Interner, Matrix, Replay};
+/// let mut interner = Interner::new();
+/// interner.extend(vec!["1C".to_owned(), "55".to_owned()]);
+/// let mut matrix = Matrix::<_, 2, 2>::new(&interner, ["1C", "55", "55", "1C"]).unwrap();
+/// matrix.select(1, 0).unwrap();
+///
+/// let replay = Replay::from_matrix(&matrix);
+/// assert_eq!(replay.to_string().parse::<Replay>().unwrap(), replay);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    width: usize,
+    height: usize,
+    rules: String,
+    moves: Vec<(usize, usize)>,
+}
+
+impl Replay {
+    /// Record the selections made so far on this matrix.
+    pub fn from_matrix<T, R, const WIDTH: usize, const HEIGHT: usize>(
+        matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+    ) -> Self
+    where
+        T: Copy,
+        R: Rules,
+    {
+        Self {
+            width: WIDTH,
+            height: HEIGHT,
+            rules: matrix.rules().name(),
+            moves: matrix.selections().to_vec(),
+        }
+    }
+
+    /// Record the path of a solution found on a `WIDTH` by `HEIGHT` matrix under the
+    /// [standard rules][Standard]. For other rules, follow with [`with_rules`][Self::with_rules].
+    ///
+    /// ```rust
+    /// # use breach_protocol::{BreachProtocol, Code, Free, Matrix, Replay, Sequence};
+    /// let codes = Code::parse_list("1C 55 55 1C").unwrap();
+    /// let mut matrix = Matrix::<_, 2, 2>::from_values(codes).unwrap().with_rules(Free);
+    /// let sequences = [Sequence::from_values(Code::parse_list("55 55").unwrap())];
+    /// let solutions = BreachProtocol::new(2).solve(&mut matrix, &sequences);
+    ///
+    /// let replay = Replay::from_solution::<2, 2>(&solutions[0]).with_rules(matrix.rules());
+    /// assert_eq!(replay.rules(), "free");
+    /// assert!(replay.verify(&matrix, &sequences, 2).is_ok());
+    /// ```
+    pub fn from_solution<const WIDTH: usize, const HEIGHT: usize>(
+        solution: &Solution<impl Copy>,
+    ) -> Self {
+        Self {
+            width: WIDTH,
+            height: HEIGHT,
+            rules: Standard::default().name(),
+            moves: solution.path().to_vec(),
+        }
+    }
+
+    /// Record that these moves were played under `rules`.
+    pub fn with_rules(self, rules: &impl Rules) -> Self {
+        Self {
+            rules: rules.name(),
+            ..self
+        }
+    }
+
+    /// The [name][Rules::name] of the rules these moves were played under
+    pub fn rules(&self) -> &str {
+        &self.rules
+    }
+
+    /// The recorded moves, in order
+    pub fn moves(&self) -> &[(usize, usize)] {
+        &self.moves
+    }
+
+    /// Replay these moves against a puzzle, checking each for legality.
+    ///
+    /// The moves are applied to a fresh copy of `matrix` as described in [`validate`], which
+    /// must have the dimensions and rules the replay was recorded with. On success, return the
+    /// final buffer and the sequences which it completes.
+    pub fn verify<T, R, const WIDTH: usize, const HEIGHT: usize>(
+        &self,
+        matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+        sequences: &[Sequence<T>],
+        buffer_size: usize,
+    ) -> Result<Solution<T>, Error>
+    where
+        T: Copy + Eq,
+        R: Rules,
+    {
+        if (self.width, self.height) != (WIDTH, HEIGHT) {
+            return Err(Error::Dimensions {
+                expected: (WIDTH, HEIGHT),
+                actual: (self.width, self.height),
+            });
+        }
+        let rules = matrix.rules().name();
+        if self.rules != rules {
+            return Err(Error::Rules {
+                expected: rules,
+                actual: self.rules.clone(),
+            });
+        }
+        validate(matrix, sequences, buffer_size, &self.moves)
+            .into_result()
+            .map_err(Error::Invalid)
+    }
+
+    fn checksum(&self) -> u32 {
+        // FNV-1a over the dimensions, rules and moves
+        let mut hash: u32 = 0x811c9dc5;
+        let fields = [self.width, self.height]
+            .into_iter()
+            .chain(self.moves.iter().flat_map(|&(x, y)| [x, y]));
+        let bytes = fields
+            .flat_map(|field| (field as u64).to_le_bytes())
+            .chain(self.rules.bytes());
+        for byte in bytes {
+            hash ^= byte as u32;
+            hash = hash.wrapping_mul(0x01000193);
+        }
+        hash
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{HEADER} {}x{} {}", self.width, self.height, self.rules)?;
+        for (x, y) in &self.moves {
+            write!(f, " {x},{y}")?;
+        }
+        write!(f, " #{:08x}", self.checksum())
+    }
+}
+
+fn parse_pair(field: &str, separator: char) -> Option<(usize, usize)> {
+    let (left, right) = field.split_once(separator)?;
+    Some((left.parse().ok()?, right.parse().ok()?))
+}
+
+impl FromStr for Replay {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_ascii_whitespace();
+
+        match fields.next() {
+            Some(HEADER) => {}
+            header => return Err(Error::Header(header.unwrap_or_default().to_owned())),
+        }
+
+        let dimensions = fields.next().ok_or(Error::Truncated)?;
+        let (width, height) =
+            parse_pair(dimensions, 'x').ok_or_else(|| Error::Field(dimensions.to_owned()))?;
+        let rules = fields.next().ok_or(Error::Truncated)?.to_owned();
+
+        let mut moves = Vec::new();
+        let checksum = loop {
+            let field = fields.next().ok_or(Error::Truncated)?;
+            if let Some(checksum) = field.strip_prefix('#') {
+                break u32::from_str_radix(checksum, 16)
+                    .map_err(|_| Error::Field(field.to_owned()))?;
+            }
+            moves.push(parse_pair(field, ',').ok_or_else(|| Error::Field(field.to_owned()))?);
+        };
+
+        if let Some(field) = fields.next() {
+            return Err(Error::Field(field.to_owned()));
+        }
+
+        let replay = Self {
+            width,
+            height,
+            rules,
+            moves,
+        };
+        let expected = replay.checksum();
+        if checksum != expected {
+            return Err(Error::Checksum {
+                expected,
+                actual: checksum,
+            });
+        }
+        Ok(replay)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("unrecognized replay header: \"{0}\"")]
+    Header(String),
+    #[error("replay ended unexpectedly")]
+    Truncated,
+    #[error("malformed replay field: \"{0}\"")]
+    Field(String),
+    #[error("replay checksum mismatch: expected {expected:08x} but found {actual:08x}")]
+    Checksum { expected: u32, actual: u32 },
+    #[error("replay was recorded on a {}x{} matrix but the puzzle is {}x{}", .actual.0, .actual.1, .expected.0, .expected.1)]
+    Dimensions {
+        expected: (usize, usize),
+        actual: (usize, usize),
+    },
+    #[error(
+        "replay was recorded under the {actual} rules but the puzzle uses the {expected} rules"
+    )]
+    Rules { expected: String, actual: String },
+    #[error("replay is not a valid path through the puzzle")]
+    Invalid(#[source] crate::validate::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        matrix::Active,
+        rules::Free,
+        test_support::{make_interner, make_matrix},
+    };
+
+    #[test]
+    fn round_trip() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        matrix.select(1, 0).unwrap();
+        matrix.select(1, 2).unwrap();
+        matrix.select(2, 2).unwrap();
+
+        let replay = Replay::from_matrix(&matrix);
+        let parsed: Replay = replay.to_string().parse().unwrap();
+        assert_eq!(parsed, replay);
+        let documented: Replay = "breach/2 5x5 standard 1,0 1,3 4,3 #7c276664"
+            .parse()
+            .unwrap();
+        assert_eq!(documented.moves(), [(1, 0), (1, 3), (4, 3)]);
+
+        let sequences = [Sequence::new(&interner, ["1A", "2B"]).unwrap()];
+        let solution = parsed
+            .verify(&make_matrix(&interner), &sequences, 4)
+            .unwrap();
+        assert_eq!(solution.path(), [(1, 0), (1, 2), (2, 2)]);
+        assert_eq!(solution.matches(), [0]);
+    }
+
+    #[test]
+    fn from_solution() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        let sequences = [Sequence::new(&interner, ["2B", "1A"]).unwrap()];
+        let solutions = crate::BreachProtocol::new(3).solve(&mut matrix, &sequences);
+
+        let replay = Replay::from_solution::<3, 3>(&solutions[0]);
+        assert_eq!((replay.width, replay.height), (3, 3));
+        assert_eq!(replay.rules(), "standard");
+        let verified = replay.verify(&matrix, &sequences, 3).unwrap();
+        assert_eq!(verified.path(), solutions[0].path());
+    }
+
+    #[test]
+    fn other_rules() {
+        let interner = make_interner();
+        let mut free = make_matrix(&interner).with_rules(Free);
+        free.select(2, 2).unwrap();
+        free.select(0, 0).unwrap();
+        let replay = Replay::from_matrix(&free);
+        let parsed: Replay = replay.to_string().parse().unwrap();
+        assert_eq!(parsed.rules(), "free");
+        assert_eq!(
+            parsed.verify(&free, &[], 3).unwrap().path(),
+            [(2, 2), (0, 0)]
+        );
+
+        // the same moves aren't checked against a matrix under the standard rules
+        assert!(matches!(
+            parsed.verify(&make_matrix(&interner), &[], 3),
+            Err(Error::Rules { expected, actual }) if expected == "standard" && actual == "free"
+        ));
+        let start = Standard { start: Active::Any };
+        assert!(matches!(
+            parsed.verify(&make_matrix(&interner).with_rules(start), &[], 3),
+            Err(Error::Rules { .. })
+        ));
+    }
+
+    #[test]
+    fn corrupted() {
+        let replay = Replay {
+            width: 3,
+            height: 3,
+            rules: "standard".to_owned(),
+            moves: vec![(1, 0), (1, 2)],
+        };
+        let corrupted = replay.to_string().replace("1,2", "1,1");
+        assert!(matches!(
+            corrupted.parse::<Replay>(),
+            Err(Error::Checksum { .. })
+        ));
+    }
+
+    #[test]
+    fn illegal_move() {
+        let interner = make_interner();
+        // (1, 0) pivots to column 1, so (2, 2) is not active
+        let replay = Replay {
+            width: 3,
+            height: 3,
+            rules: "standard".to_owned(),
+            moves: vec![(1, 0), (2, 2)],
+        };
+        assert!(matches!(
+            replay.verify(&make_matrix(&interner), &[], 4),
+            Err(Error::Invalid(crate::validate::Error::IllegalMove {
+                step: 1,
+                ..
+            }))
+        ));
+    }
+}
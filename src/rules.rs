@@ -0,0 +1,109 @@
+use crate::matrix::Active;
+
+/// The rules which decide which cells may be selected.
+///
+/// A [`Matrix`][crate::Matrix] begins with the [`initial`][Rules::initial] active set. A cell
+/// may be selected if it is a member of the active set and hasn't already been selected; the
+/// active set then becomes whatever [`next`][Rules::next] returns. The solver, validators and
+/// renderers all follow the rules of the matrix they are given.
+///
+/// ```rust
+/// # use breach_protocol::{Active, Matrix, Rules};
+/// /// Selections alternate between the diagonals through each selected cell.
+/// #[derive(Debug, Clone)]
+/// struct Diagonals;
+///
+/// impl Rules for Diagonals {
+///     fn initial(&self) -> Active {
+///         Active::Diagonal(0)
+///     }
+///
+///     fn next(&self, active: Active, x: usize, y: usize) -> Active {
+///         match active {
+///             Active::Diagonal(_) => Active::AntiDiagonal(x + y),
+///             _ => Active::Diagonal(x as isize - y as isize),
+///         }
+///     }
+///
+///     fn name(&self) -> String {
+///         "diagonals".to_owned()
+///     }
+/// }
+///
+/// let mut matrix = Matrix::<_, 3, 3>::from_values(0..9).unwrap().with_rules(Diagonals);
+/// assert!(matrix.select(1, 0).is_err());
+/// matrix.select(1, 1).unwrap();
+/// assert_eq!(matrix.active(), Active::AntiDiagonal(2));
+/// assert!(matrix.select(2, 2).is_err());
+/// matrix.select(0, 2).unwrap();
+/// ```
+pub trait Rules: Clone {
+    /// The active set before anything has been selected.
+    fn initial(&self) -> Active;
+
+    /// The active set after selecting `(x, y)` from `active`.
+    ///
+    /// This is only called for cells which are members of `active`.
+    fn next(&self, active: Active, x: usize, y: usize) -> Active;
+
+    /// A name identifying these rules, recorded by a [`Replay`][crate::Replay] so that it is
+    /// only verified under the same rules.
+    ///
+    /// Rules which behave differently must have different names, and names must not contain
+    /// whitespace.
+    fn name(&self) -> String;
+}
+
+/// The rules of the game as it is usually played: selections alternate between the row and
+/// the column of the previous selection.
+///
+/// By default, the first selection is from the top row. Other starting sets can be chosen; for
+/// example, starting from [`Active::Any`] allows the first selection to be made anywhere. Only
+/// rows and columns [pivot][Active::toggle], so a selection from any other starting set acts as
+/// though it was made from a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Standard {
+    pub start: Active,
+}
+
+impl Rules for Standard {
+    fn initial(&self) -> Active {
+        self.start
+    }
+
+    fn next(&self, active: Active, x: usize, y: usize) -> Active {
+        match active {
+            Active::Row(_) | Active::Column(_) => active
+                .toggle(x, y)
+                .expect("rules are only consulted for members of the active set"),
+            Active::Diagonal(_) | Active::AntiDiagonal(_) | Active::Any => Active::Column(x),
+        }
+    }
+
+    /// `standard` when starting from the top row, otherwise also naming the starting set.
+    fn name(&self) -> String {
+        if self.start == Active::default() {
+            "standard".to_owned()
+        } else {
+            format!("standard:{:?}", self.start)
+        }
+    }
+}
+
+/// Any cell may be selected at any time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Free;
+
+impl Rules for Free {
+    fn initial(&self) -> Active {
+        Active::Any
+    }
+
+    fn next(&self, _active: Active, _x: usize, _y: usize) -> Active {
+        Active::Any
+    }
+
+    fn name(&self) -> String {
+        "free".to_owned()
+    }
+}
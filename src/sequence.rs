@@ -1,17 +1,135 @@
-use crate::interner::{InternedString, Interner};
+use std::{fmt, ops::Range, str::FromStr};
+
+use crate::interner::Lookup;
 
 #[derive(Debug)]
-pub struct Sequence<'a> {
+pub struct Sequence<T> {
     pub name: Option<String>,
-    items: Vec<InternedString<'a>>,
+    items: Vec<Pattern<T>>,
+}
+
+/// A single item of a [`Sequence`], which matches one or more tokens.
+///
+/// Patterns have a text form, produced by `Display` and parsed by `FromStr` and
+/// [`Sequence::parse`]: `*` matches any token, and tokens joined by `|` match any one of them.
+///
+/// ```rust
+/// # use breach_protocol::{Code, Pattern, Sequence};
+/// let code = |s: &str| s.parse::<Code>().unwrap();
+/// // 1C, then anything, then either BD or 55
+/// let sequence = Sequence::from_patterns([
+///     Pattern::Exact(code("1C")),
+///     Pattern::Any,
+///     Pattern::OneOf(vec![code("BD"), code("55")]),
+/// ]);
+/// assert!(sequence.is_matched(&Code::parse_list("1C E9 55").unwrap()));
+/// assert!(!sequence.is_matched(&Code::parse_list("1C E9 1C").unwrap()));
+/// assert_eq!(sequence.to_string(), "1C * BD|55");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern<T> {
+    /// Match exactly this token.
+    Exact(T),
+    /// Match any token.
+    Any,
+    /// Match any one of these tokens. An empty list matches nothing.
+    OneOf(Vec<T>),
+}
+
+impl<T: PartialEq> Pattern<T> {
+    /// `true` when this pattern matches the token.
+    pub fn matches(&self, value: &T) -> bool {
+        match self {
+            Pattern::Exact(expect) => expect == value,
+            Pattern::Any => true,
+            Pattern::OneOf(options) => options.contains(value),
+        }
+    }
+}
+
+impl<T> Pattern<T> {
+    /// Apply `f` to each token of this pattern.
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Pattern<U> {
+        match self {
+            Pattern::Exact(value) => Pattern::Exact(f(value)),
+            Pattern::Any => Pattern::Any,
+            Pattern::OneOf(options) => Pattern::OneOf(options.into_iter().map(f).collect()),
+        }
+    }
+
+    /// The tokens this pattern names explicitly: none for [`Pattern::Any`].
+    pub(crate) fn tokens(&self) -> &[T] {
+        match self {
+            Pattern::Exact(value) => std::slice::from_ref(value),
+            Pattern::Any => &[],
+            Pattern::OneOf(options) => options,
+        }
+    }
+
+    /// Parse the text form of a pattern, converting each token with `parse`.
+    fn parse_with<E>(s: &str, mut parse: impl FnMut(&str) -> Result<T, E>) -> Result<Self, E> {
+        if s == "*" {
+            Ok(Pattern::Any)
+        } else if s.contains('|') {
+            s.split('|')
+                .map(parse)
+                .collect::<Result<_, _>>()
+                .map(Pattern::OneOf)
+        } else {
+            parse(s).map(Pattern::Exact)
+        }
+    }
+}
+
+impl<T> From<T> for Pattern<T> {
+    fn from(value: T) -> Self {
+        Pattern::Exact(value)
+    }
+}
+
+/// Exact tokens display as themselves, wildcards as `*`, and alternatives joined by `|`.
+impl<T: fmt::Display> fmt::Display for Pattern<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Exact(value) => value.fmt(f),
+            Pattern::Any => f.write_str("*"),
+            Pattern::OneOf(options) => {
+                for (idx, option) in options.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str("|")?;
+                    }
+                    option.fmt(f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// `*` parses as [`Pattern::Any`], tokens joined by `|` as [`Pattern::OneOf`], and anything else
+/// as a single token. The empty [`Pattern::OneOf`] has no text form.
+///
+/// ```rust
+/// # use breach_protocol::{Code, Pattern};
+/// let pattern: Pattern<Code> = "1C|55".parse().unwrap();
+/// assert_eq!(pattern, Pattern::OneOf(vec![Code(0x1C), Code(0x55)]));
+/// assert_eq!(pattern.to_string(), "1C|55");
+/// ```
+impl<T: FromStr> FromStr for Pattern<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, str::parse)
+    }
 }
 
-/// Convert an iterable of stringy things into a vector of interned strings.
-pub(crate) fn make_interned<Items, Item>(
-    interner: &Interner<String>,
+/// Convert an iterable of stringy things into a vector of tokens.
+pub(crate) fn make_interned<L, Items, Item>(
+    interner: L,
     items: Items,
-) -> Result<Vec<InternedString>, Error>
+) -> Result<Vec<L::Token>, Error>
 where
+    L: Lookup,
     Items: IntoIterator<Item = Item>,
     Item: AsRef<str>,
 {
@@ -19,7 +137,7 @@ where
     for item in items.into_iter() {
         let item = item.as_ref();
         let interned = interner
-            .get(item)
+            .lookup(item)
             .ok_or_else(|| Error::NotFound(item.to_owned()))?;
         interneds.push(interned);
     }
@@ -27,65 +145,143 @@ where
 }
 
 /// A Sequence is an ordered collection of values which, if selected, gives rewards.
-impl<'a> Sequence<'a> {
-    pub fn new<Items, Item>(interner: &'a Interner<String>, items: Items) -> Result<Self, Error>
+impl<T> Sequence<T> {
+    /// Create a sequence from an iterable of stringy things, looking up each item's token through
+    /// `interner`. Every item matches exactly its token; for wildcards and alternatives, see
+    /// [`parse`][Self::parse].
+    pub fn new<L, Items, Item>(interner: L, items: Items) -> Result<Self, Error>
     where
+        L: Lookup<Token = T>,
         Items: IntoIterator<Item = Item>,
         Item: AsRef<str>,
     {
-        let items = make_interned(interner, items)?;
-        Ok(Self { name: None, items })
-    }
-
-    /// `true` when this sequence matches some subset if the iterable.
-    pub fn is_matched(&self, iter: impl IntoIterator<Item = InternedString<'a>>) -> bool {
-        // the basic strategy here is to construct a vector of booleans.
-        // each bool in the vec is true if and only if this sequence matches at that offset.
-        // we can then update each bool efficiently as new items arrive.
-        //
-        // example: our desired sequence is 1A 2B 1A 3C
-        // iterator items are 1A 2B 3C 1A 2B 1A 3C
-        //
-        // item 0: 1A. Index 0 - offset 0 means we check our sequence at position 0. Match, so we push `true`
-        // Item 1: 2B. First, do index 1 - offset 1, so check sequence at position 0. No match, so push `false`.
-        //              Next, do checks for offset in 0..(n-1):
-        //                  index 1 - offset 0: match && true = true
-        // Item 2: 3C. First, do index 2 - offset 2, so check sequence at position 0. No match, so push `false`.
-        //              Next, do checks for offset in 0..(n-1):
-        //                  index 2 - offset 0: no match (we want 1A again here); true && false = false
-        //                  index 2 - offset 1: no match (we want 2B here); false && false = false
-        // etc...
-        let mut sequences = Vec::new();
-        let mut item_count = 0;
-        'outer: for (index, item) in iter.into_iter().enumerate() {
-            item_count += 1;
-
-            sequences.push(self.items[0] == item);
-
-            // now do the updates
-            for offset in 0..(sequences.len() - 1) {
-                let check_idx = match index.checked_sub(offset) {
-                    Some(idx) => idx,
-                    None => continue 'outer,
-                };
-                if check_idx >= self.items.len() {
-                    continue 'outer;
-                }
-                sequences[offset] = sequences[offset] && self.items[check_idx] == item;
-            }
+        make_interned(interner, items).map(Self::from_values)
+    }
+
+    /// Create a sequence from the text form of each [`Pattern`], looking up its tokens through
+    /// `interner`.
+    ///
+    /// ```rust
+    /// # use breach_protocol::{Interner, Pattern, Sequence};
+    /// let interner = Interner::from_iter(["1C", "55", "BD"].map(String::from));
+    /// let sequence = Sequence::parse(&interner, ["1C", "*", "55|BD"]).unwrap();
+    /// assert_eq!(sequence.items()[1], Pattern::Any);
+    /// assert_eq!(sequence.to_string(), "1C * 55|BD");
+    /// ```
+    pub fn parse<L, Items, Item>(interner: L, items: Items) -> Result<Self, Error>
+    where
+        L: Lookup<Token = T>,
+        Items: IntoIterator<Item = Item>,
+        Item: AsRef<str>,
+    {
+        let lookup = |token: &str| {
+            interner
+                .lookup(token)
+                .ok_or_else(|| Error::NotFound(token.to_owned()))
+        };
+        items
+            .into_iter()
+            .map(|item| Pattern::parse_with(item.as_ref(), lookup))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::from_patterns)
+    }
+}
+
+impl<T> Sequence<T> {
+    /// Create a sequence from an iterable of tokens.
+    pub fn from_values(items: impl IntoIterator<Item = T>) -> Self {
+        Self::from_patterns(items.into_iter().map(Pattern::Exact))
+    }
+
+    /// Create a sequence from an iterable of patterns, each of which matches one token.
+    pub fn from_patterns(items: impl IntoIterator<Item = Pattern<T>>) -> Self {
+        Self {
+            name: None,
+            items: items.into_iter().collect(),
         }
+    }
+
+    /// The patterns which make up this sequence, in order
+    pub fn items(&self) -> &[Pattern<T>] {
+        &self.items
+    }
 
-        // we're not quite done: what if there was a partial match at the end,
-        // but they didn't provide enough input?
-        let desired_length = item_count - self.items.len() + 1;
-        sequences.truncate(desired_length);
+    /// `true` when this sequence appears, in order and contiguously, within the buffer.
+    ///
+    /// An empty sequence is never matched: there is nothing to select to complete it.
+    pub fn is_matched(&self, buffer: &[T]) -> bool
+    where
+        T: PartialEq,
+    {
+        self.find_matches(buffer).next().is_some()
+    }
+
+    /// Iterate over the ranges of `buffer` in which this sequence appears, in order of position.
+    ///
+    /// Matches may overlap. An empty sequence, or one longer than the buffer, has no matches.
+    ///
+    /// ```rust
+    /// # use breach_protocol::{Code, Sequence};
+    /// let sequence = Sequence::from_values(Code::parse_list("1C 1C").unwrap());
+    /// let buffer = Code::parse_list("1C 1C 1C 55").unwrap();
+    /// assert_eq!(sequence.find_matches(&buffer).collect::<Vec<_>>(), [0..2, 1..3]);
+    /// ```
+    pub fn find_matches<'b>(&'b self, buffer: &'b [T]) -> impl 'b + Iterator<Item = Range<usize>>
+    where
+        T: PartialEq,
+    {
+        let len = self.items.len();
+        // `windows` panics on 0, and an empty sequence matches nothing anyway
+        buffer
+            .windows(len.max(1))
+            .enumerate()
+            .filter(move |(_, window)| len > 0 && self.matches_prefix(window))
+            .map(move |(start, _)| start..start + len)
+    }
 
-        // if any subseqence was true at this point, we've succeeded
-        sequences.into_iter().any(std::convert::identity)
+    /// The length of the longest prefix of this sequence with which `buffer` ends.
+    ///
+    /// This is how much of the sequence has been entered if the next selections are to complete
+    /// it. It equals the length of the sequence when the buffer ends with the whole sequence,
+    /// and is 0 for an empty sequence. It can never exceed the length of the buffer.
+    pub fn progress(&self, buffer: &[T]) -> usize
+    where
+        T: PartialEq,
+    {
+        (0..=self.items.len().min(buffer.len()))
+            .rev()
+            .find(|&len| self.matches_prefix(&buffer[buffer.len() - len..]))
+            .unwrap_or_default()
+    }
+
+    /// `true` when the values match the first patterns of this sequence, one for one.
+    fn matches_prefix(&self, values: &[T]) -> bool
+    where
+        T: PartialEq,
+    {
+        values.len() <= self.items.len()
+            && values
+                .iter()
+                .zip(&self.items)
+                .all(|(value, pattern)| pattern.matches(value))
+    }
+}
+
+/// Sequences display as their patterns separated by spaces, without their name.
+impl<T: fmt::Display> fmt::Display for Sequence<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, item) in self.items.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" ")?;
+            }
+            item.fmt(f)?;
+        }
+        Ok(())
     }
 }
 
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
     #[error("item not found when constructing sequence: \"{0}\"")]
     NotFound(String),
@@ -94,26 +290,130 @@ pub enum Error {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::make_interner;
     use rstest::rstest;
 
-    fn make_interner() -> Interner<String> {
-        let mut interner = Interner::new();
-        interner.insert("1A".into());
-        interner.insert("2B".into());
-        interner.insert("3C".into());
-        interner
-    }
-
     #[rstest]
+    #[case::short_buffer("1A 2B 3C", "1A", false)]
     #[case::bare("1A 2B 1A 3C", "1A 2B 3C 1A 2B 1A 3C", true)]
     #[case::incomplete("1A 2B 1A 3C", "1A 2B 3C 1A 2B 1A", false)]
     #[case::extra("1A 2B 1A 3C", "1A 2B 3C 1A 2B 1A 3C 2B 3C 1A", true)]
     #[case::exact("1A 2B", "1A 2B", true)]
     #[case::backwards("1A 2B 3C", "3C 2B 1A", false)]
+    #[case::late_offset("2B 1A", "1A 2B 3C", false)]
     fn inner_example(#[case] sequence: &str, #[case] items: &str, #[case] expect_match: bool) {
         let interner = make_interner();
         let sequence = Sequence::new(&interner, sequence.split_ascii_whitespace()).unwrap();
         let items = make_interned(&interner, items.split_ascii_whitespace()).unwrap();
-        assert_eq!(sequence.is_matched(items), expect_match);
+        assert_eq!(sequence.is_matched(&items), expect_match);
+    }
+
+    #[rstest]
+    #[case::single("1A 2B", "3C 1A 2B 3C", &[(1, 3)])]
+    #[case::overlapping("1A 1A", "1A 1A 1A", &[(0, 2), (1, 3)])]
+    #[case::oversize("1A 2B 3C", "1A 2B", &[])]
+    #[case::empty("", "1A 2B", &[])]
+    #[case::empty_buffer("1A", "", &[])]
+    fn match_positions(
+        #[case] sequence: &str,
+        #[case] items: &str,
+        #[case] expect: &[(usize, usize)],
+    ) {
+        let interner = make_interner();
+        let sequence = Sequence::new(&interner, sequence.split_ascii_whitespace()).unwrap();
+        let items = make_interned(&interner, items.split_ascii_whitespace()).unwrap();
+        let found: Vec<_> = sequence
+            .find_matches(&items)
+            .map(|range| (range.start, range.end))
+            .collect();
+        assert_eq!(found, expect);
+        assert_eq!(sequence.is_matched(&items), !expect.is_empty());
+    }
+
+    #[rstest]
+    #[case::partial("1A 2B 3C", "3C 1A 2B", 2)]
+    #[case::complete("1A 2B", "3C 1A 2B", 2)]
+    #[case::none("1A 2B", "1A 3C", 0)]
+    #[case::longest_prefix("1A 1A 2B", "1A 1A 1A", 2)]
+    #[case::oversize("1A 2B 3C", "1A", 1)]
+    #[case::empty("", "1A", 0)]
+    #[case::empty_buffer("1A", "", 0)]
+    fn progress(#[case] sequence: &str, #[case] items: &str, #[case] expect: usize) {
+        let interner = make_interner();
+        let sequence = Sequence::new(&interner, sequence.split_ascii_whitespace()).unwrap();
+        let items = make_interned(&interner, items.split_ascii_whitespace()).unwrap();
+        assert_eq!(sequence.progress(&items), expect);
+    }
+
+    #[rstest]
+    #[case::wildcard("1A 3C 2B", &[(0, 3)], 3)]
+    #[case::alternative("1A 3C 1A", &[], 1)]
+    #[case::partial("2B 1A 2B", &[], 2)]
+    #[case::overlapping("1A 1A 2B 3C", &[(0, 3), (1, 4)], 3)]
+    fn patterns(#[case] items: &str, #[case] expect: &[(usize, usize)], #[case] progress: usize) {
+        let interner = make_interner();
+        let get = |value| interner.get(value).unwrap();
+        // 1A, then anything, then 2B or 3C
+        let sequence = Sequence::from_patterns([
+            Pattern::Exact(get("1A")),
+            Pattern::Any,
+            Pattern::OneOf(vec![get("2B"), get("3C")]),
+        ]);
+        let items = make_interned(&interner, items.split_ascii_whitespace()).unwrap();
+        let found: Vec<_> = sequence
+            .find_matches(&items)
+            .map(|range| (range.start, range.end))
+            .collect();
+        assert_eq!(found, expect);
+        assert_eq!(sequence.progress(&items), progress);
+    }
+
+    #[rstest]
+    #[case::exact("1C")]
+    #[case::any("*")]
+    #[case::one_of("1C|55")]
+    #[case::one_of_many("1C|55|BD")]
+    fn pattern_round_trip(#[case] text: &str) {
+        let pattern: Pattern<crate::Code> = text.parse().unwrap();
+        assert_eq!(pattern.to_string(), text);
+        assert_eq!(pattern.to_string().parse::<Pattern<_>>().unwrap(), pattern);
+    }
+
+    #[test]
+    fn parse_patterns() {
+        let interner = make_interner();
+        let sequence = Sequence::parse(&interner, ["1A", "*", "2B|3C"]).unwrap();
+        let get = |value| interner.get(value).unwrap();
+        assert_eq!(
+            sequence.items(),
+            [
+                Pattern::Exact(get("1A")),
+                Pattern::Any,
+                Pattern::OneOf(vec![get("2B"), get("3C")]),
+            ]
+        );
+        assert_eq!(sequence.to_string(), "1A * 2B|3C");
+        let reparsed = Sequence::parse(&interner, sequence.to_string().split(' ')).unwrap();
+        assert_eq!(reparsed.items(), sequence.items());
+
+        assert!(matches!(
+            Sequence::parse(&interner, ["1A|FF"]),
+            Err(Error::NotFound(item)) if item == "FF"
+        ));
+        assert!("1C|XY".parse::<Pattern<crate::Code>>().is_err());
+    }
+
+    #[test]
+    fn new_is_literal() {
+        let mut interner = make_interner();
+        interner.extend(vec!["*".to_owned(), "1A|2B".to_owned()]);
+        let get = |value| interner.get(value).unwrap();
+        let sequence = Sequence::new(&interner, ["*", "1A|2B"]).unwrap();
+        assert_eq!(
+            sequence.items(),
+            [Pattern::Exact(get("*")), Pattern::Exact(get("1A|2B"))]
+        );
+        assert!(sequence.is_matched(&[get("*"), get("1A|2B")]));
+        assert!(!sequence.is_matched(&[get("3C"), get("1A")]));
     }
 }
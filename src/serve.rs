@@ -0,0 +1,412 @@
+// Local HTTP JSON solving service, built with the `serve` feature.
+
+use std::{
+    io::{self, Read},
+    net::{SocketAddr, ToSocketAddrs},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{
+    owned::{describe, OwnedPuzzle, OwnedSolution},
+    validate,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PuzzleRequest {
+    matrix: Vec<Vec<String>>,
+    buffer_size: usize,
+    #[serde(default)]
+    sequences: Vec<Vec<String>>,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    path: Option<Vec<(usize, usize)>>,
+    #[serde(default)]
+    blocked: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Serialize)]
+struct SolutionResponse {
+    path: Vec<(usize, usize)>,
+    buffer: Vec<String>,
+    matches: Vec<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct SolveResponse {
+    solutions: Vec<SolutionResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct ValidateResponse {
+    valid: bool,
+    error: Option<String>,
+    failed_step: Option<usize>,
+    #[serde(flatten)]
+    solution: SolutionResponse,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// A request failure, reported to the client with the given status.
+struct Failure {
+    status: u16,
+    error: String,
+}
+
+impl Failure {
+    fn bad_request(err: impl std::error::Error) -> Self {
+        Self {
+            status: 400,
+            error: describe(&err),
+        }
+    }
+}
+
+impl PuzzleRequest {
+    fn puzzle(&self) -> Result<OwnedPuzzle, Failure> {
+        let height = self.matrix.len();
+        let width = self.matrix.first().map_or(0, Vec::len);
+        if self.matrix.iter().any(|row| row.len() != width) {
+            return Err(Failure {
+                status: 400,
+                error: "matrix rows must all have the same length".into(),
+            });
+        }
+        let values = self.matrix.iter().flatten().cloned().collect();
+        let mut puzzle = OwnedPuzzle::new(values, width, height, self.buffer_size)
+            .map_err(Failure::bad_request)?;
+        for sequence in &self.sequences {
+            puzzle
+                .add_sequence(sequence.clone())
+                .map_err(Failure::bad_request)?;
+        }
+        for &(x, y) in &self.blocked {
+            puzzle.block(x, y).map_err(Failure::bad_request)?;
+        }
+        Ok(puzzle)
+    }
+}
+
+fn solution_response(puzzle: &OwnedPuzzle, solution: OwnedSolution) -> SolutionResponse {
+    SolutionResponse {
+        buffer: solution
+            .path
+            .iter()
+            .map(|&(x, y)| puzzle.value(x, y).to_owned())
+            .collect(),
+        path: solution.path,
+        matches: solution.matches,
+    }
+}
+
+fn solve(request: PuzzleRequest) -> Result<String, Failure> {
+    let puzzle = request.puzzle()?;
+    let mut solutions = puzzle.solve().map_err(Failure::bad_request)?;
+    // more matches first; among equal counts, prefer later sequences
+    solutions.sort_by(|a, b| {
+        b.matches
+            .len()
+            .cmp(&a.matches.len())
+            .then_with(|| b.matches.iter().rev().cmp(a.matches.iter().rev()))
+    });
+    if let Some(limit) = request.limit {
+        solutions.truncate(limit);
+    }
+    let response = SolveResponse {
+        solutions: solutions
+            .into_iter()
+            .map(|solution| solution_response(&puzzle, solution))
+            .collect(),
+    };
+    Ok(serde_json::to_string(&response).expect("responses always serialize"))
+}
+
+fn check_path(request: PuzzleRequest) -> Result<String, Failure> {
+    let puzzle = request.puzzle()?;
+    let path = request.path.as_deref().ok_or_else(|| Failure {
+        status: 400,
+        error: "missing field `path`".into(),
+    })?;
+    let (solution, error) = puzzle.check_path(path).map_err(Failure::bad_request)?;
+    let response = ValidateResponse {
+        valid: error.is_none(),
+        failed_step: error.as_ref().map(|err| match err {
+            validate::Error::BufferOverflow { step, .. }
+            | validate::Error::IllegalMove { step, .. } => *step,
+        }),
+        error: error.as_ref().map(|err| describe(err)),
+        solution: solution_response(&puzzle, solution),
+    };
+    Ok(serde_json::to_string(&response).expect("responses always serialize"))
+}
+
+fn route(request: &mut Request, max_buffer_size: usize) -> Result<String, Failure> {
+    // the query string, if any, plays no part in routing
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+    let handler = match path.as_str() {
+        "/solve" => solve,
+        "/validate" => check_path,
+        _ => {
+            return Err(Failure {
+                status: 404,
+                error: format!("no such endpoint: {path}"),
+            })
+        }
+    };
+    if request.method() != &Method::Post {
+        return Err(Failure {
+            status: 405,
+            error: format!("{path} only accepts POST"),
+        });
+    }
+
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body)
+        .map_err(Failure::bad_request)?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err(Failure {
+            status: 413,
+            error: format!("request body exceeds the maximum of {MAX_BODY_BYTES} bytes"),
+        });
+    }
+    let puzzle: PuzzleRequest = serde_json::from_str(&body).map_err(Failure::bad_request)?;
+    if puzzle.buffer_size > max_buffer_size {
+        return Err(Failure {
+            status: 400,
+            error: format!(
+                "buffer_size {} exceeds the maximum of {max_buffer_size}",
+                puzzle.buffer_size
+            ),
+        });
+    }
+    handler(puzzle)
+}
+
+fn respond(mut request: Request, max_buffer_size: usize) {
+    let (status, body) = match route(&mut request, max_buffer_size) {
+        Ok(body) => (200, body),
+        Err(failure) => (
+            failure.status,
+            serde_json::to_string(&ErrorResponse {
+                error: failure.error,
+            })
+            .expect("responses always serialize"),
+        ),
+    };
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("header is valid");
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(content_type);
+    // the client may have gone away; there's nobody to report that to
+    let _ = request.respond(response);
+}
+
+/// Answer requests from `server` forever.
+fn handle(server: &Server, max_buffer_size: usize) -> ! {
+    loop {
+        match server.recv() {
+            Ok(request) => respond(request, max_buffer_size),
+            Err(err) => {
+                // typically a transient accept failure, such as running out of file descriptors
+                log::warn!("failed to receive a request: {err}");
+                thread::sleep(RECV_BACKOFF);
+            }
+        }
+    }
+}
+
+/// The largest request body accepted. The largest supported puzzle fits in a fraction of this.
+const MAX_BODY_BYTES: u64 = 64 * 1024;
+
+/// How long a worker waits after failing to receive a request before trying again.
+const RECV_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A local HTTP JSON solving service.
+///
+/// ## Endpoints
+///
+/// `POST /solve` takes a puzzle and returns its solutions, best first:
+///
+/// ```json
+/// { "matrix": [["1C", "55"], ["55", "1C"]], "buffer_size": 2, "sequences": [["1C", "55"]] }
+/// ```
+///
+/// Sequence items may also be `"*"`, matching any token, or tokens joined by `|`, such as
+/// `"1C|55"`, matching any one of them.
+///
+/// An optional `"blocked"` field lists `[x, y]` points which may not be selected.
+///
+/// An optional `"limit"` field caps the number of solutions returned. Solutions are ranked by
+/// the number of sequences they complete, then by which sequences those are: as in the game,
+/// later sequences are assumed to be the more valuable.
+///
+/// `POST /validate` takes a puzzle with an additional `"path"` field, a list of `[x, y]`
+/// points, and reports whether it is legal as [`validate`][crate::validate] does.
+///
+/// Errors are reported with a 4xx status and a body of the form `{ "error": "..." }`. Puzzles
+/// whose `buffer_size` exceeds [`Service::max_buffer_size`] are rejected with a 400, and request
+/// bodies over 64 KiB with a 413.
+///
+/// Failures to accept a connection are logged through the [`log`] crate.
+pub struct Service {
+    server: Arc<Server>,
+    max_buffer_size: usize,
+}
+
+impl Service {
+    /// The largest buffer a request may ask for unless configured otherwise.
+    ///
+    /// Solving takes time exponential in the buffer size, so unbounded sizes would let a single
+    /// request occupy a worker indefinitely.
+    pub const DEFAULT_MAX_BUFFER_SIZE: usize = 10;
+
+    /// Bind the service to the given address. Use port 0 to choose any free port.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let server = Server::http(addr).map_err(io::Error::other)?;
+        Ok(Self {
+            server: Arc::new(server),
+            max_buffer_size: Self::DEFAULT_MAX_BUFFER_SIZE,
+        })
+    }
+
+    /// Reject puzzles with buffers larger than `size`. Defaults to [`Self::DEFAULT_MAX_BUFFER_SIZE`].
+    pub fn max_buffer_size(mut self, size: usize) -> Self {
+        self.max_buffer_size = size;
+        self
+    }
+
+    /// The address on which the service is listening.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.server
+            .server_addr()
+            .to_ip()
+            .expect("service always listens on an IP address")
+    }
+
+    /// Handle requests on `workers` threads. This never returns.
+    pub fn run(self, workers: usize) -> ! {
+        for _ in 1..workers.max(1) {
+            let server = self.server.clone();
+            thread::spawn(move || handle(&server, self.max_buffer_size));
+        }
+        handle(&self.server, self.max_buffer_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+    };
+
+    fn start() -> SocketAddr {
+        let service = Service::bind("127.0.0.1:0").unwrap();
+        let addr = service.local_addr();
+        thread::spawn(move || service.run(1));
+        addr
+    }
+
+    /// Make a request and return the status code and body.
+    fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, body.to_owned())
+    }
+
+    const PUZZLE: &str = r#""matrix": [["1A", "2B", "3C"], ["2B", "3C", "1A"], ["3C", "1A", "2B"]],
+        "buffer_size": 3,
+        "sequences": [["2B", "1A"], ["3C", "1A"]]"#;
+
+    #[test]
+    fn solve() {
+        let addr = start();
+        let (status, body) = request(addr, "POST", "/solve", &format!("{{{PUZZLE}}}"));
+        assert_eq!(status, 200, "{body}");
+        let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let solutions = response["solutions"].as_array().unwrap();
+        assert!(!solutions.is_empty());
+        // the sequences can't share a buffer of 3, so the later sequence ranks first
+        assert_eq!(solutions[0]["matches"], serde_json::json!([1]));
+        let (_, limited) = request(
+            addr,
+            "POST",
+            "/solve",
+            &format!("{{{PUZZLE}, \"limit\": 1}}"),
+        );
+        let limited: serde_json::Value = serde_json::from_str(&limited).unwrap();
+        assert_eq!(limited["solutions"].as_array().unwrap().len(), 1);
+        let (_, blocked) = request(
+            addr,
+            "POST",
+            "/solve",
+            &format!("{{{PUZZLE}, \"blocked\": [[1, 0]]}}"),
+        );
+        let blocked: serde_json::Value = serde_json::from_str(&blocked).unwrap();
+        for solution in blocked["solutions"].as_array().unwrap() {
+            assert_ne!(solution["path"][0], serde_json::json!([1, 0]));
+        }
+        // query strings don't affect routing
+        let (status, body) = request(addr, "POST", "/solve?pretty", &format!("{{{PUZZLE}}}"));
+        assert_eq!(status, 200, "{body}");
+    }
+
+    #[test]
+    fn validate() {
+        let addr = start();
+        let body = format!("{{{PUZZLE}, \"path\": [[1, 0], [2, 2]]}}");
+        let (status, body) = request(addr, "POST", "/validate", &body);
+        assert_eq!(status, 200, "{body}");
+        let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(response["valid"], false);
+        assert_eq!(response["failed_step"], 1);
+        assert_eq!(response["path"], serde_json::json!([[1, 0]]));
+    }
+
+    #[test]
+    fn errors() {
+        let addr = start();
+        assert_eq!(request(addr, "GET", "/solve", "").0, 405);
+        assert_eq!(request(addr, "POST", "/nope", "{}").0, 404);
+        let (status, body) = request(addr, "POST", "/solve", "{\"matrix\": []}");
+        assert_eq!(status, 400);
+        assert!(body.contains("buffer_size"), "{body}");
+        let body = format!("{{{PUZZLE}}}").replace("\"buffer_size\": 3", "\"buffer_size\": 11");
+        let (status, body) = request(addr, "POST", "/solve", &body);
+        assert_eq!(status, 400);
+        assert!(body.contains("maximum of 10"), "{body}");
+        let padding = " ".repeat(MAX_BODY_BYTES as usize);
+        let (status, body) = request(addr, "POST", "/solve", &format!("{{{PUZZLE}}}{padding}"));
+        assert_eq!(status, 413);
+        assert!(body.contains("maximum of 65536 bytes"), "{body}");
+    }
+}
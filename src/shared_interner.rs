@@ -0,0 +1,283 @@
+use std::{
+    borrow::Borrow,
+    collections::{hash_map::RandomState, HashMap},
+    fmt,
+    hash::{BuildHasher, Hash},
+    ops::Deref,
+    sync::{Arc, RwLock},
+};
+
+use crate::interner::Symbol;
+
+/// The number of independently locked shards in a [`SharedInterner`].
+const SHARDS: usize = 16;
+
+#[derive(Debug)]
+struct Shard<T> {
+    values: Vec<Arc<T>>,
+    // Values are found by hash, so that lookups can borrow as `Q` the way `HashMap` does.
+    // The buckets are almost always of length 1.
+    buckets: HashMap<u64, Vec<u32>>,
+}
+
+impl<T> Default for Shard<T> {
+    fn default() -> Self {
+        Self {
+            values: Vec::new(),
+            buckets: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Shard<T> {
+    fn find<Q>(&self, hash: u64, value: &Q) -> Option<u32>
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.buckets.get(&hash).and_then(|bucket| {
+            bucket
+                .iter()
+                .copied()
+                .find(|&local| (*self.values[local as usize]).borrow() == value)
+        })
+    }
+}
+
+/// An append-only interner which can be shared between threads.
+///
+/// Unlike [`Interner`][crate::Interner], values are inserted through a shared reference, so a
+/// `SharedInterner` can be put in an `Arc` or a `static` and used from many threads at once.
+/// Values are spread across several independently locked shards; lookups take only a read lock
+/// on a single shard, and insertions a write lock on a single shard.
+///
+/// As with [`StableInterner`][crate::StableInterner], values are never renumbered, so the
+/// [`Symbol`] of a [`Shared`] handle stays valid for the life of the interner.
+///
+/// ```rust
+/// # use breach_protocol::SharedInterner;
+/// let interner = SharedInterner::new();
+/// std::thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| interner.insert("1C".to_owned()));
+///     }
+/// });
+/// assert_eq!(interner.len(), 1);
+/// assert_eq!(*interner.get("1C").unwrap(), "1C");
+/// ```
+pub struct SharedInterner<T> {
+    hasher: RandomState,
+    shards: [RwLock<Shard<T>>; SHARDS],
+}
+
+impl<T> Default for SharedInterner<T> {
+    fn default() -> Self {
+        Self {
+            hasher: RandomState::new(),
+            shards: std::array::from_fn(|_| RwLock::default()),
+        }
+    }
+}
+
+impl<T> fmt::Debug for SharedInterner<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedInterner")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T> SharedInterner<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of distinct values in this interner
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().expect("interner lock poisoned").values.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The handle for the value at `local` in `shard`, whose lock is held by the caller.
+    fn shared(&self, shard: &Shard<T>, shard_idx: usize, local: u32) -> Shared<'_, T> {
+        let value = Arc::as_ptr(&shard.values[local as usize]);
+        Shared {
+            interner: self,
+            id: local as usize * SHARDS + shard_idx,
+            // SAFETY: the interner is append-only, so the `Arc` holding this value is never
+            // dropped while the interner is borrowed, and the value never moves with it.
+            value: unsafe { &*value },
+        }
+    }
+
+    /// Get the handle for a symbol, if it was produced by this interner.
+    pub fn resolve(&self, symbol: Symbol) -> Option<Shared<'_, T>> {
+        let id = symbol.id() as usize;
+        let (shard, local) = (id % SHARDS, id / SHARDS);
+        let guard = self.shards[shard].read().expect("interner lock poisoned");
+        (local < guard.values.len()).then(|| self.shared(&guard, shard, local as u32))
+    }
+}
+
+impl<T> SharedInterner<T>
+where
+    T: Hash + Eq,
+{
+    fn hash<Q: Hash + ?Sized>(&self, value: &Q) -> u64 {
+        self.hasher.hash_one(value)
+    }
+
+    /// Get the handle for the provided value, if it is available in the interner.
+    pub fn get<Q>(&self, value: &Q) -> Option<Shared<'_, T>>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash(value);
+        let shard = hash as usize % SHARDS;
+        let guard = self.shards[shard].read().expect("interner lock poisoned");
+        let local = guard.find(hash, value)?;
+        Some(self.shared(&guard, shard, local))
+    }
+
+    /// Insert the provided value into this interner, returning its handle.
+    ///
+    /// If the value was already present, its existing handle is returned.
+    pub fn insert(&self, value: T) -> Shared<'_, T> {
+        let hash = self.hash(&value);
+        let shard_idx = hash as usize % SHARDS;
+        let mut shard = self.shards[shard_idx]
+            .write()
+            .expect("interner lock poisoned");
+        if let Some(local) = shard.find(hash, &value) {
+            return self.shared(&shard, shard_idx, local);
+        }
+        let local = shard.values.len();
+        assert!(
+            local * SHARDS + shard_idx <= u32::MAX as usize,
+            "interner holds fewer than 2^32 values"
+        );
+        let local = local as u32;
+        shard.values.push(Arc::new(value));
+        shard.buckets.entry(hash).or_default().push(local);
+        self.shared(&shard, shard_idx, local)
+    }
+}
+
+/// A value interned in a [`SharedInterner`].
+///
+/// Like [`Interned`][crate::Interned], handles are `Copy` whatever the value, and compare
+/// equal only if they come from the same interner and refer to the same value. The value is
+/// resolved when the handle is made, so reading it takes no lock.
+pub struct Shared<'a, T> {
+    interner: &'a SharedInterner<T>,
+    id: usize,
+    value: &'a T,
+}
+
+impl<'a, T> Shared<'a, T> {
+    pub(crate) fn interner(&self) -> &'a SharedInterner<T> {
+        self.interner
+    }
+
+    /// A handle to this value which doesn't borrow the interner.
+    pub fn symbol(&self) -> Symbol {
+        Symbol(self.id as u32)
+    }
+
+    /// The value of this handle, borrowed for as long as the interner.
+    pub fn get(&self) -> &'a T {
+        self.value
+    }
+}
+
+impl<'a, T> fmt::Debug for Shared<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shared").field("id", &self.id).finish()
+    }
+}
+
+impl<'a, T> Deref for Shared<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl<'a, T> PartialEq for Shared<'a, T> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.interner, other.interner) && self.id == other.id
+    }
+}
+
+impl<'a, T> Eq for Shared<'a, T> {}
+
+impl<'a, T> Clone for Shared<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Shared<'a, T> {}
+
+impl<'a, T> fmt::Display for Shared<'a, T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn interner_is_send_sync() {
+        assert_send_sync::<SharedInterner<String>>();
+        assert_send_sync::<Shared<'static, String>>();
+    }
+
+    #[test]
+    fn concurrent_inserts() {
+        let interner = SharedInterner::new();
+        let values: Vec<_> = (0..=255_u8).map(|n| format!("{n:02X}")).collect();
+        let handles: Vec<Vec<_>> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| values.iter().map(|v| interner.insert(v.clone())).collect())
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+
+        assert_eq!(interner.len(), 256);
+        for worker in &handles[1..] {
+            assert_eq!(worker, &handles[0]);
+        }
+        for (value, handle) in values.iter().zip(&handles[0]) {
+            assert_eq!(handle.get(), value);
+            assert_eq!(interner.get(value.as_str()), Some(*handle));
+            assert_eq!(interner.resolve(handle.symbol()), Some(*handle));
+        }
+
+        // reading a handle doesn't wait on its shard
+        let handle = handles[0][0];
+        let guard = interner.shards[handle.id % SHARDS].write().unwrap();
+        assert_eq!(*handle, "00");
+        drop(guard);
+
+        let other = SharedInterner::new();
+        assert_ne!(other.insert("1C".to_owned()), interner.get("1C").unwrap());
+    }
+}
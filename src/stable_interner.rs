@@ -0,0 +1,169 @@
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::interner::Symbol;
+
+/// An append-only interner whose symbols never change once assigned.
+///
+/// Where [`Interner`][crate::Interner] keeps its values sorted, renumbering them as values are
+/// inserted, a stable interner assigns ids in insertion order and finds values by hashing.
+/// Insertion and lookup are both O(1), and a [`Symbol`] from a stable interner remains valid for
+/// as long as the interner exists, so its id can be stored in files and caches.
+///
+/// Each value is stored twice, once for lookup by id and once for lookup by value.
+///
+/// ```rust
+/// # use breach_protocol::StableInterner;
+/// let mut interner = StableInterner::new();
+/// let bd = interner.insert("BD".to_owned());
+/// let c1 = interner.insert("1C".to_owned());
+///
+/// // ids are assigned in insertion order and never change
+/// assert_eq!((bd.id(), c1.id()), (0, 1));
+/// assert_eq!(interner.get("BD"), Some(bd));
+///
+/// // sorted iteration is available on demand
+/// let sorted: Vec<_> = interner.iter_sorted().map(|(_, value)| value.as_str()).collect();
+/// assert_eq!(sorted, ["1C", "BD"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StableInterner<T> {
+    values: Vec<T>,
+    ids: HashMap<T, Symbol>,
+}
+
+impl<T> Default for StableInterner<T> {
+    fn default() -> Self {
+        Self {
+            values: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl<T> StableInterner<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of distinct values in this interner
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Get the value of a symbol, if it was produced by this interner.
+    pub fn resolve(&self, symbol: Symbol) -> Option<&T> {
+        self.values.get(symbol.id() as usize)
+    }
+
+    /// Iterate over the symbols and values of this interner, in insertion order.
+    pub fn iter(&self) -> impl '_ + Iterator<Item = (Symbol, &T)> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| (Symbol(idx as u32), value))
+    }
+
+    /// Iterate over the symbols and values of this interner, in sorted order of the values.
+    ///
+    /// This sorts on every call, so it costs O(n log n).
+    pub fn iter_sorted(&self) -> impl '_ + Iterator<Item = (Symbol, &T)>
+    where
+        T: Ord,
+    {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_unstable_by_key(|&(_, value)| value);
+        entries.into_iter()
+    }
+}
+
+impl<T> StableInterner<T>
+where
+    T: Hash + Eq,
+{
+    /// Get the symbol for the provided value, if it is available in the interner.
+    pub fn get<Q>(&self, value: &Q) -> Option<Symbol>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.ids.get(value).copied()
+    }
+
+    /// Insert the provided value into this interner, returning its symbol.
+    ///
+    /// If the value was already present, its existing symbol is returned.
+    pub fn insert(&mut self, value: T) -> Symbol
+    where
+        T: Clone,
+    {
+        if let Some(&symbol) = self.ids.get(&value) {
+            return symbol;
+        }
+        let symbol = Symbol(
+            u32::try_from(self.values.len()).expect("interner holds fewer than 2^32 values"),
+        );
+        self.ids.insert(value.clone(), symbol);
+        self.values.push(value);
+        symbol
+    }
+}
+
+impl<T> Extend<T> for StableInterner<T>
+where
+    T: Hash + Eq + Clone,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        for value in values {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for StableInterner<T>
+where
+    T: Hash + Eq + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut interner = Self::new();
+        interner.extend(values);
+        interner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_stable() {
+        let mut interner = StableInterner::new();
+        let ff = interner.insert("FF".to_owned());
+        let e9 = interner.insert("E9".to_owned());
+        // inserting a value which sorts first doesn't renumber anything
+        let c1 = interner.insert("1C".to_owned());
+        assert_eq!(interner.insert("FF".to_owned()), ff);
+
+        assert_eq!(interner.len(), 3);
+        assert_eq!(interner.resolve(ff).unwrap(), "FF");
+        assert_eq!(interner.resolve(e9).unwrap(), "E9");
+        assert_eq!(interner.get("1C"), Some(c1));
+        assert_eq!(interner.resolve(Symbol::from_id(ff.id())).unwrap(), "FF");
+        assert_eq!(interner.resolve(Symbol::from_id(3)), None);
+    }
+
+    #[test]
+    fn iteration_order() {
+        let interner: StableInterner<_> = ["FF", "E9", "1C", "E9"].into_iter().collect();
+        let inserted: Vec<_> = interner.iter().map(|(_, value)| *value).collect();
+        assert_eq!(inserted, ["FF", "E9", "1C"]);
+        let sorted: Vec<_> = interner
+            .iter_sorted()
+            .map(|(symbol, value)| (symbol.id(), *value))
+            .collect();
+        assert_eq!(sorted, [(2, "1C"), (1, "E9"), (0, "FF")]);
+    }
+}
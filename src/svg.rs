@@ -0,0 +1,277 @@
+use std::fmt::{self, Write};
+
+use crate::{
+    grid::Grid,
+    matrix::{Active, Matrix},
+    rules::Rules,
+    sequence::Sequence,
+    Solution,
+};
+
+const CELL: usize = 48;
+const MARGIN: usize = 16;
+const LINE_HEIGHT: usize = 24;
+
+/// Escape text for inclusion in SVG content or attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The pixel coordinates of the center of a cell.
+fn center(x: usize, y: usize) -> (usize, usize) {
+    (MARGIN + x * CELL + CELL / 2, MARGIN + y * CELL + CELL / 2)
+}
+
+/// Render a matrix and a solution on it as a standalone SVG document.
+///
+/// The diagram shows the grid of values with blocked cells crossed out, the active line at each
+/// step of the solution, the numbered selections joined by arrows, and the list of sequences,
+/// with those the solution completes marked.
+pub fn render_svg<T, R, const WIDTH: usize, const HEIGHT: usize>(
+    matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+    solution: &Solution<T>,
+    sequences: &[Sequence<T>],
+) -> String
+where
+    T: Copy + fmt::Display,
+    R: Rules,
+{
+    let mut svg = String::new();
+    write_svg(&mut svg, matrix, solution, sequences).expect("writing to a string cannot fail");
+    svg
+}
+
+fn write_svg<T, R, const WIDTH: usize, const HEIGHT: usize>(
+    svg: &mut String,
+    matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+    solution: &Solution<T>,
+    sequences: &[Sequence<T>],
+) -> fmt::Result
+where
+    T: Copy + fmt::Display,
+    R: Rules,
+{
+    let grid_width = WIDTH * CELL;
+    let grid_height = HEIGHT * CELL;
+    let width = grid_width + 2 * MARGIN;
+    let height = grid_height + 2 * MARGIN + (sequences.len() + 1) * LINE_HEIGHT;
+
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="monospace">"#
+    )?;
+    writeln!(
+        svg,
+        r##"<defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="6" markerHeight="6" orient="auto-start-reverse"><path d="M 0 0 L 10 5 L 0 10 z" fill="#d03030"/></marker></defs>"##
+    )?;
+    writeln!(
+        svg,
+        r##"<rect width="{width}" height="{height}" fill="#101018"/>"##
+    )?;
+
+    // active lines, one per step; later lines are drawn over earlier ones
+    writeln!(
+        svg,
+        r##"<g class="active" fill="#e0e040" fill-opacity="0.08" stroke="#e0e040" stroke-opacity="0.5" stroke-dasharray="4 4">"##
+    )?;
+    let rules = matrix.rules();
+    let mut active = rules.initial();
+    for &(x, y) in solution.path() {
+        let rects = match active {
+            Active::Row(row) => vec![(MARGIN, MARGIN + row * CELL, grid_width, CELL)],
+            Active::Column(column) => vec![(MARGIN + column * CELL, MARGIN, CELL, grid_height)],
+            // other sets are outlined cell by cell
+            _ => Grid::<T, WIDTH, HEIGHT>::line_points(active)
+                .map(|(x, y)| (MARGIN + x * CELL, MARGIN + y * CELL, CELL, CELL))
+                .collect(),
+        };
+        for (left, top, w, h) in rects {
+            writeln!(
+                svg,
+                r#"<rect x="{left}" y="{top}" width="{w}" height="{h}"/>"#
+            )?;
+        }
+        if !active.contains(x, y) {
+            // an illegal path can't be drawn meaningfully past this point
+            break;
+        }
+        active = rules.next(active, x, y);
+    }
+    writeln!(svg, "</g>")?;
+
+    // blocked cells, shaded and crossed out beneath their values
+    writeln!(
+        svg,
+        r##"<g class="blocked" fill="#301818" stroke="#803030" stroke-width="2">"##
+    )?;
+    for (x, y) in Grid::<T, WIDTH, HEIGHT>::points().filter(|&(x, y)| matrix.is_blocked(x, y)) {
+        let (left, top) = (MARGIN + x * CELL, MARGIN + y * CELL);
+        let (right, bottom) = (left + CELL, top + CELL);
+        writeln!(
+            svg,
+            r#"<rect x="{left}" y="{top}" width="{CELL}" height="{CELL}"/><path d="M {left} {top} L {right} {bottom} M {right} {top} L {left} {bottom}"/>"#
+        )?;
+    }
+    writeln!(svg, "</g>")?;
+
+    // the grid of values
+    writeln!(
+        svg,
+        r##"<g class="grid" font-size="16" text-anchor="middle" dominant-baseline="central" fill="#c0f0c0">"##
+    )?;
+    for ((x, y), value) in matrix.values().cells() {
+        let (cx, cy) = center(x, y);
+        let opacity = if matrix.is_blocked(x, y) {
+            r#" opacity="0.4""#
+        } else {
+            ""
+        };
+        writeln!(
+            svg,
+            r#"<text x="{cx}" y="{cy}"{opacity}>{}</text>"#,
+            escape(&value.to_string())
+        )?;
+    }
+    writeln!(svg, "</g>")?;
+
+    // arrows between steps
+    writeln!(
+        svg,
+        r##"<g class="path" stroke="#d03030" stroke-width="2" marker-end="url(#arrow)">"##
+    )?;
+    let radius = CELL * 3 / 8;
+    for pair in solution.path().windows(2) {
+        let (x1, y1) = center(pair[0].0, pair[0].1);
+        let (x2, y2) = center(pair[1].0, pair[1].1);
+        // shorten each arrow so that it runs between the circles rather than their centers
+        let (dx, dy) = (x2 as f64 - x1 as f64, y2 as f64 - y1 as f64);
+        let length = dx.hypot(dy);
+        if length == 0.0 {
+            continue;
+        }
+        let (ux, uy) = (dx / length * radius as f64, dy / length * radius as f64);
+        writeln!(
+            svg,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}"/>"#,
+            x1 as f64 + ux,
+            y1 as f64 + uy,
+            x2 as f64 - ux,
+            y2 as f64 - uy,
+        )?;
+    }
+    writeln!(svg, "</g>")?;
+
+    // numbered selections
+    writeln!(
+        svg,
+        r##"<g class="selections" font-size="11" text-anchor="middle" fill="#f0a0a0">"##
+    )?;
+    for (step, &(x, y)) in solution.path().iter().enumerate() {
+        let (cx, cy) = center(x, y);
+        writeln!(
+            svg,
+            r##"<circle cx="{cx}" cy="{cy}" r="{radius}" fill="none" stroke="#d03030" stroke-width="2"/><text x="{}" y="{}">{}</text>"##,
+            cx + radius,
+            cy - radius,
+            step + 1
+        )?;
+    }
+    writeln!(svg, "</g>")?;
+
+    // sequence list
+    writeln!(svg, r#"<g class="sequences" font-size="14">"#)?;
+    let list_top = MARGIN + grid_height + LINE_HEIGHT;
+    for (idx, sequence) in sequences.iter().enumerate() {
+        let completed = solution.matches().contains(&idx);
+        let (marker, fill) = if completed {
+            ("\u{2714}", "#60e060")
+        } else {
+            ("\u{2717}", "#808080")
+        };
+        let items = sequence
+            .items()
+            .iter()
+            .map(|item| escape(&item.to_string()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let name = sequence
+            .name
+            .as_deref()
+            .map(|name| format!("{}: ", escape(name)))
+            .unwrap_or_default();
+        writeln!(
+            svg,
+            r#"<text x="{MARGIN}" y="{}" fill="{fill}">{marker} {name}{items}</text>"#,
+            list_top + idx * LINE_HEIGHT
+        )?;
+    }
+    writeln!(svg, "</g>")?;
+
+    writeln!(svg, "</svg>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interner::Interner,
+        test_support::{make_interner, make_matrix},
+        validate::validate,
+    };
+
+    #[test]
+    fn renders_solution() {
+        let mut interner = Interner::new();
+        interner.extend(vec!["1A".into(), "2B".into(), "<&>".into()]);
+        let matrix = Matrix::<_, 3, 3>::new(
+            &interner,
+            "1A 2B <&> 2B <&> 1A <&> 1A 2B".split_ascii_whitespace(),
+        )
+        .unwrap();
+        let sequences = [
+            Sequence::new(&interner, ["2B", "1A"]).unwrap(),
+            Sequence::new(&interner, ["<&>", "<&>"]).unwrap(),
+        ];
+        let solution = validate(&matrix, &sequences, 3, &[(1, 0), (1, 2)])
+            .into_result()
+            .unwrap();
+
+        let svg = render_svg(&matrix, &solution, &sequences);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("&lt;&amp;&gt;"));
+        assert!(!svg.contains("<&>"));
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert_eq!(svg.matches("\u{2714}").count(), 1);
+        assert!(!svg.contains("opacity=\"0.4\""));
+    }
+
+    #[test]
+    fn renders_blocked_cells() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        matrix.block(0, 0).unwrap();
+        matrix.block(2, 1).unwrap();
+        let solution = validate(&matrix, &[], 3, &[(1, 0)]).into_result().unwrap();
+
+        let svg = render_svg(&matrix, &solution, &[]);
+        let (_, blocked) = svg.split_once("<g class=\"blocked\"").unwrap();
+        let (blocked, _) = blocked.split_once("</g>").unwrap();
+        assert_eq!(blocked.matches("<rect").count(), 2);
+        assert!(blocked.contains(r#"<rect x="16" y="16" width="48" height="48"/>"#));
+        assert!(blocked.contains(r#"<rect x="112" y="64" width="48" height="48"/>"#));
+        assert_eq!(svg.matches("opacity=\"0.4\"").count(), 2);
+    }
+}
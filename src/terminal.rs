@@ -0,0 +1,264 @@
+use std::fmt::{self, Write};
+
+use crate::{
+    grid::Grid,
+    matrix::{Active, Matrix},
+    rules::Rules,
+    sequence::Sequence,
+};
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const BOLD: &str = "\x1b[1m";
+const HIGHLIGHT: &str = "\x1b[30;43m";
+const GREEN: &str = "\x1b[32m";
+const STRIKE: &str = "\x1b[9;31m";
+
+/// How text renderings are decorated.
+///
+/// In either style, blocked cells are shown in brackets.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Plain text, suitable for logs.
+    ///
+    /// The active line is marked in the row and column headers.
+    #[default]
+    Plain,
+    /// ANSI escape sequences, suitable for terminals.
+    ///
+    /// The active line is highlighted, chosen cells are dimmed, and blocked cells are struck
+    /// through.
+    Ansi,
+}
+
+impl Style {
+    fn paint(self, f: &mut dyn Write, codes: &[&str], text: &str) -> fmt::Result {
+        match self {
+            Style::Ansi if !codes.is_empty() => {
+                for code in codes {
+                    f.write_str(code)?;
+                }
+                write!(f, "{text}{RESET}")
+            }
+            _ => f.write_str(text),
+        }
+    }
+}
+
+fn write_grid<T, R, const WIDTH: usize, const HEIGHT: usize>(
+    f: &mut dyn Write,
+    matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+    style: Style,
+) -> fmt::Result
+where
+    T: Copy + fmt::Display,
+    R: Rules,
+{
+    let order = |x, y| {
+        matrix
+            .selections()
+            .iter()
+            .position(|&point| point == (x, y))
+    };
+
+    // each cell holds the value, and if chosen, its position in the selection order; blocked
+    // cells are bracketed in every style
+    let cell = |x, y| {
+        let value = matrix.get(x, y).expect("point is in bounds");
+        match order(x, y) {
+            Some(step) => format!("{value}#{}", step + 1),
+            None if matrix.is_blocked(x, y) => format!("[{value}]"),
+            None => value.to_string(),
+        }
+    };
+    let cell_width = Grid::<T, WIDTH, HEIGHT>::points()
+        .map(|(x, y)| cell(x, y).chars().count())
+        .max()
+        .unwrap_or_default();
+    let label_width = HEIGHT.saturating_sub(1).to_string().len();
+
+    let active = matrix.active();
+    let marker = |is_active| if is_active { '>' } else { ' ' };
+
+    // lines are assembled before writing so that padding doesn't leave trailing whitespace
+    let mut line = String::new();
+
+    // column headers
+    write!(line, "{:label_width$}  ", "")?;
+    for x in 0..WIDTH {
+        let header = match active {
+            Active::Column(column) if column == x => "v".to_owned(),
+            _ => x.to_string(),
+        };
+        write!(line, " {header:^cell_width$}")?;
+    }
+    writeln!(f, "{}", line.trim_end())?;
+
+    for y in 0..HEIGHT {
+        line.clear();
+        write!(
+            line,
+            "{y:>label_width$}{} ",
+            marker(active == Active::Row(y))
+        )?;
+        for x in 0..WIDTH {
+            let mut codes = Vec::new();
+            if matrix.is_blocked(x, y) {
+                codes.push(STRIKE);
+            } else if active.contains(x, y) {
+                codes.push(HIGHLIGHT);
+            }
+            if matrix.is_chosen(x, y) {
+                codes.push(DIM);
+            }
+            line.push(' ');
+            style.paint(&mut line, &codes, &format!("{:^cell_width$}", cell(x, y)))?;
+        }
+        writeln!(f, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Render the state of a game for display in a terminal.
+///
+/// This shows the grid, with the active line, the selection order and any blocked cells marked;
+/// the buffer; and each sequence, marked with its progress against the end of the buffer.
+pub fn render_terminal<T, R, const WIDTH: usize, const HEIGHT: usize>(
+    matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+    sequences: &[Sequence<T>],
+    buffer_size: usize,
+    style: Style,
+) -> String
+where
+    T: Copy + Eq + fmt::Display,
+    R: Rules,
+{
+    let mut out = String::new();
+    write_terminal(&mut out, matrix, sequences, buffer_size, style)
+        .expect("writing to a string cannot fail");
+    out
+}
+
+fn write_terminal<T, R, const WIDTH: usize, const HEIGHT: usize>(
+    f: &mut dyn Write,
+    matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+    sequences: &[Sequence<T>],
+    buffer_size: usize,
+    style: Style,
+) -> fmt::Result
+where
+    T: Copy + Eq + fmt::Display,
+    R: Rules,
+{
+    write_grid(f, matrix, style)?;
+    writeln!(f)?;
+
+    let buffer: Vec<_> = matrix.selected_values().collect();
+    f.write_str("buffer:")?;
+    for value in &buffer {
+        f.write_char(' ')?;
+        style.paint(f, &[BOLD], &value.to_string())?;
+    }
+    for _ in buffer.len()..buffer_size {
+        f.write_str(" __")?;
+    }
+    writeln!(f)?;
+
+    for sequence in sequences {
+        let items = sequence.items();
+        let (marker, codes): (_, &[&str]) = if sequence.is_matched(&buffer) {
+            ("[done]".to_owned(), &[GREEN])
+        } else {
+            (
+                format!("[{}/{}]", sequence.progress(&buffer), items.len()),
+                &[],
+            )
+        };
+        style.paint(f, codes, &marker)?;
+        if let Some(name) = &sequence.name {
+            write!(f, " {name}:")?;
+        }
+        for item in items {
+            write!(f, " {item}")?;
+        }
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+/// Matrices display as a plain-text grid; see [`render_terminal`] for richer output.
+impl<T, R, const WIDTH: usize, const HEIGHT: usize> fmt::Display for Matrix<T, WIDTH, HEIGHT, R>
+where
+    T: Copy + fmt::Display,
+    R: Rules,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_grid(f, self, Style::Plain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{make_interner, make_matrix};
+
+    #[test]
+    fn plain() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        matrix.select(1, 0).unwrap();
+        matrix.select(1, 2).unwrap();
+        let sequences = [
+            Sequence::new(&interner, ["2B", "1A"]).unwrap(),
+            Sequence::new(&interner, ["1A", "3C", "3C"]).unwrap(),
+            Sequence::new(&interner, ["3C", "3C"]).unwrap(),
+        ];
+
+        let expect = "     0    1    2
+0    1A  2B#1  3C
+1    2B   3C   1A
+2>   3C  1A#2  2B
+
+buffer: 2B 1A __
+[done] 2B 1A
+[1/3] 1A 3C 3C
+[0/2] 3C 3C
+";
+        assert_eq!(
+            render_terminal(&matrix, &sequences, 3, Style::Plain),
+            expect
+        );
+    }
+
+    #[test]
+    fn ansi_highlights_active_line() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        matrix.select(1, 0).unwrap();
+        let rendered = render_terminal(&matrix, &[], 3, Style::Ansi);
+        // column 1 is active, one cell of which is chosen
+        assert_eq!(rendered.matches(HIGHLIGHT).count(), 3);
+        assert_eq!(rendered.matches(DIM).count(), 1);
+        assert!(!render_terminal(&matrix, &[], 3, Style::Plain).contains('\x1b'));
+    }
+
+    #[test]
+    fn blocked_cells() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        matrix.block(1, 0).unwrap();
+        matrix.block(2, 1).unwrap();
+
+        let expect = "     0    1    2
+0>   1A  [2B]  3C
+1    2B   3C  [1A]
+2    3C   1A   2B
+";
+        assert_eq!(matrix.to_string(), expect);
+
+        let rendered = render_terminal(&matrix, &[], 3, Style::Ansi);
+        assert_eq!(rendered.matches(STRIKE).count(), 2);
+        // the blocked cell of the active row isn't highlighted as selectable
+        assert_eq!(rendered.matches(HIGHLIGHT).count(), 2);
+    }
+}
@@ -0,0 +1,28 @@
+//! Fixtures shared by the unit tests of several modules.
+
+use crate::{
+    interner::{InternedString, Interner},
+    matrix::Matrix,
+};
+
+/// An interner holding the tokens of [`make_matrix`].
+pub(crate) fn make_interner() -> Interner<String> {
+    let mut interner = Interner::new();
+    interner.extend(vec!["1A".into(), "2B".into(), "3C".into()]);
+    interner
+}
+
+/// A 3x3 matrix in which every row and column holds each of `1A`, `2B` and `3C` once:
+///
+/// ```text
+/// 1A 2B 3C
+/// 2B 3C 1A
+/// 3C 1A 2B
+/// ```
+pub(crate) fn make_matrix(interner: &Interner<String>) -> Matrix<InternedString<'_>, 3, 3> {
+    Matrix::new(
+        interner,
+        "1A 2B 3C 2B 3C 1A 3C 1A 2B".split_ascii_whitespace(),
+    )
+    .unwrap()
+}
@@ -0,0 +1,158 @@
+use crate::{
+    matrix::{self, Matrix},
+    rules::Rules,
+    sequence::Sequence,
+    Solution,
+};
+
+/// The result of [validating][validate] a path against a puzzle.
+#[derive(Debug)]
+pub struct Validation<T> {
+    solution: Solution<T>,
+    error: Option<Error>,
+}
+
+impl<T> Validation<T> {
+    /// `true` when every step of the path was legal
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The first illegal step of the path, if any
+    pub fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    /// The buffer produced by the legal steps of the path, and the sequences which it completes.
+    ///
+    /// If the path contained an illegal step, this covers only the steps preceding it.
+    pub fn solution(&self) -> &Solution<T> {
+        &self.solution
+    }
+
+    /// Split into the solution produced by the legal steps, and the first illegal step if any.
+    pub fn into_parts(self) -> (Solution<T>, Option<Error>) {
+        (self.solution, self.error)
+    }
+
+    /// Convert into the solution, or the first illegal step.
+    pub fn into_result(self) -> Result<Solution<T>, Error> {
+        match self.error {
+            None => Ok(self.solution),
+            Some(err) => Err(err),
+        }
+    }
+}
+
+/// Check a path of selections against a puzzle.
+///
+/// Selection begins from the initial active set of the matrix's [`Rules`] with an empty buffer,
+/// regardless of any selections already made on `matrix`, which is not modified.
+///
+/// Validation stops at the first illegal step.
+pub fn validate<T, R, const WIDTH: usize, const HEIGHT: usize>(
+    matrix: &Matrix<T, WIDTH, HEIGHT, R>,
+    sequences: &[Sequence<T>],
+    buffer_size: usize,
+    path: &[(usize, usize)],
+) -> Validation<T>
+where
+    T: Copy + Eq,
+    R: Rules,
+{
+    let mut matrix = matrix.clone();
+    matrix.reset();
+
+    let mut error = None;
+    for (step, &(x, y)) in path.iter().enumerate() {
+        if step >= buffer_size {
+            error = Some(Error::BufferOverflow { step, buffer_size });
+            break;
+        }
+        if let Err(source) = matrix.select(x, y) {
+            error = Some(Error::IllegalMove { step, source });
+            break;
+        }
+    }
+
+    Validation {
+        solution: Solution::new(&matrix, sequences),
+        error,
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("step {step} exceeds the buffer size of {buffer_size}")]
+    BufferOverflow { step: usize, buffer_size: usize },
+    #[error("step {step} is illegal")]
+    IllegalMove {
+        step: usize,
+        #[source]
+        source: matrix::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{make_interner, make_matrix};
+
+    #[test]
+    fn valid_path() {
+        let interner = make_interner();
+        let matrix = make_matrix(&interner);
+        let sequences = [
+            Sequence::new(&interner, ["2B", "1A"]).unwrap(),
+            Sequence::new(&interner, ["3C", "3C"]).unwrap(),
+        ];
+        let validation = validate(&matrix, &sequences, 3, &[(1, 0), (1, 2), (0, 2)]);
+        assert!(validation.is_valid());
+        assert_eq!(validation.solution().matches(), [0]);
+    }
+
+    #[test]
+    fn first_illegal_step() {
+        let interner = make_interner();
+        let matrix = make_matrix(&interner);
+        let validation = validate(&matrix, &[], 4, &[(1, 0), (1, 0), (2, 2)]);
+        assert!(matches!(
+            validation.error(),
+            Some(Error::IllegalMove {
+                step: 1,
+                source: matrix::Error::AlreadySelected { x: 1, y: 0 }
+            })
+        ));
+        assert_eq!(validation.solution().path(), [(1, 0)]);
+    }
+
+    #[test]
+    fn starts_from_row_zero() {
+        let interner = make_interner();
+        let mut matrix = make_matrix(&interner);
+        matrix.select(0, 0).unwrap();
+        let validation = validate(&matrix, &[], 4, &[(0, 1)]);
+        assert!(matches!(
+            validation.error(),
+            Some(Error::IllegalMove {
+                step: 0,
+                source: matrix::Error::NotActive { .. }
+            })
+        ));
+    }
+
+    #[test]
+    fn buffer_overflow() {
+        let interner = make_interner();
+        let matrix = make_matrix(&interner);
+        let validation = validate(&matrix, &[], 1, &[(1, 0), (1, 1)]);
+        assert!(matches!(
+            validation.error(),
+            Some(Error::BufferOverflow {
+                step: 1,
+                buffer_size: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,84 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "breach_protocol.h"
+
+#define CHECK(expr)                                                          \
+  do {                                                                       \
+    if (!(expr)) {                                                           \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #expr); \
+      exit(1);                                                               \
+    }                                                                        \
+  } while (0)
+
+#define CHECK_OK(expr)                                                       \
+  do {                                                                       \
+    BpError err_ = (expr);                                                   \
+    if (err_ != BP_ERROR_OK) {                                               \
+      fprintf(stderr, "%s:%d: %s failed: %s\n", __FILE__, __LINE__, #expr,  \
+              bp_error_message(err_));                                       \
+      exit(1);                                                               \
+    }                                                                        \
+  } while (0)
+
+int main(void) {
+  const char *tokens[] = {
+      "1C", "55", "BD", "E9",
+      "55", "BD", "1C", "1C",
+      "E9", "1C", "55", "BD",
+      "BD", "E9", "E9", "55",
+  };
+  BpPuzzle *puzzle = NULL;
+  CHECK_OK(bp_puzzle_new(tokens, 4, 4, 3, &puzzle));
+
+  const char *sequence[] = {"55", "E9"};
+  CHECK_OK(bp_puzzle_add_sequence(puzzle, sequence, 2));
+
+  const char *missing[] = {"FF"};
+  CHECK(bp_puzzle_add_sequence(puzzle, missing, 1) == BP_ERROR_NOT_FOUND);
+
+  /* (1, 0) pivots to column 1, so (2, 2) is not active */
+  size_t xs[] = {1, 2};
+  size_t ys[] = {0, 2};
+  size_t failed_step = 0;
+  CHECK(bp_puzzle_check_path(puzzle, xs, ys, 2, &failed_step) == BP_ERROR_NOT_ACTIVE);
+  CHECK(failed_step == 1);
+
+  BpSolutions *solutions = NULL;
+  CHECK_OK(bp_solve(puzzle, &solutions));
+  size_t len = bp_solutions_len(solutions);
+  CHECK(len > 0);
+
+  for (size_t solution = 0; solution < len; solution++) {
+    size_t path_len = bp_solution_path_len(solutions, solution);
+    CHECK(path_len == 3);
+    for (size_t step = 0; step < path_len; step++) {
+      size_t x, y;
+      CHECK_OK(bp_solution_point(solutions, solution, step, &x, &y));
+      CHECK(x < 4 && y < 4);
+    }
+    size_t matched;
+    CHECK(bp_solution_matches_len(solutions, solution) == 1);
+    CHECK_OK(bp_solution_match(solutions, solution, 0, &matched));
+    CHECK(matched == 0);
+  }
+
+  size_t x, y;
+  CHECK(bp_solution_point(solutions, len, 0, &x, &y) == BP_ERROR_INDEX_OUT_OF_RANGE);
+
+  bp_solutions_free(solutions);
+
+  CHECK_OK(bp_puzzle_block(puzzle, 1, 0));
+  CHECK_OK(bp_puzzle_block(puzzle, 1, 0));
+  CHECK(bp_puzzle_block(puzzle, 4, 0) == BP_ERROR_OUT_OF_BOUNDS);
+  CHECK(bp_puzzle_check_path(puzzle, xs, ys, 2, &failed_step) == BP_ERROR_BLOCKED);
+  CHECK(failed_step == 0);
+
+  bp_puzzle_free(puzzle);
+
+  CHECK(bp_puzzle_new(tokens, 9, 1, 3, &puzzle) == BP_ERROR_UNSUPPORTED_SIZE);
+  CHECK(puzzle == NULL);
+
+  printf("ok\n");
+  return 0;
+}
@@ -0,0 +1,58 @@
+//! Build and run the C test program against the shared library.
+
+// `extension-module` leaves the Python symbols for the interpreter to provide, so the library
+// can't be linked into a standalone program.
+#![cfg(all(unix, not(feature = "extension-module")))]
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The directory containing the shared library built alongside this test.
+fn library_dir() -> PathBuf {
+    // test executables and the library they link live in `target/<profile>/deps`
+    let exe = env::current_exe().unwrap();
+    exe.parent().unwrap().to_owned()
+}
+
+#[test]
+fn c_program() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib_dir = library_dir();
+    let library = lib_dir.join(format!(
+        "{}breach_protocol{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    ));
+    assert!(library.exists(), "{} was not built", library.display());
+
+    let program = lib_dir.join("ffi_test");
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".into()))
+        .arg(manifest_dir.join("tests/c/ffi_test.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lbreach_protocol")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("failed to run C compiler");
+    assert!(status.success(), "compiling the C test program failed");
+
+    // cargo puts other build directories on the library path, which may hold a stale build
+    let output = Command::new(&program)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "C test program failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}